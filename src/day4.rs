@@ -1,18 +1,27 @@
-use std::collections::HashMap;
-use std::num::ParseIntError;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
 use std::str::FromStr;
 
-use chrono::DateTime;
-use chrono::Duration;
-use chrono::Timelike;
-use chrono::Utc;
 use regex::Regex;
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+pub mod duty;
+pub mod export;
+pub mod forecast;
+pub mod monitor;
+pub mod timestamp;
+
+use self::timestamp::LogTimestamp;
+use self::timestamp::ParseTimestampError;
+
+// Variants are declared in the order events within the same minute are
+// replayed: a shift starts before its guard can fall asleep, and a guard
+// falls asleep before waking up
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
 pub enum EventType {
     BeganShift,
-    WokeUp,
     FellAsleep,
+    WokeUp,
 }
 
 // Event represents an action a guard took
@@ -20,11 +29,38 @@ pub enum EventType {
 pub struct Event {
     id: i32,
     kind: EventType,
-    timestamp: DateTime<Utc>,
+    timestamp: LogTimestamp,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ParseEventError {
+    // The line doesn't start with a valid [YYYY-MM-DD HH:MM] timestamp
+    Timestamp(ParseTimestampError),
+    // What follows the timestamp isn't a shift change, nap or wake up
+    UnknownContent(String),
+}
+
+impl fmt::Display for ParseEventError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseEventError::Timestamp(e) => write!(f, "{}", e),
+            ParseEventError::UnknownContent(content) => {
+                write!(f, "unknown event {:?}", content)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseEventError {}
+
+impl From<ParseTimestampError> for ParseEventError {
+    fn from(e: ParseTimestampError) -> Self {
+        ParseEventError::Timestamp(e)
+    }
 }
 
 impl FromStr for Event {
-    type Err = ParseIntError;
+    type Err = ParseEventError;
 
     /*
       [1518-11-22 23:54] Guard #1237 begins shift
@@ -33,41 +69,10 @@ impl FromStr for Event {
     */
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         lazy_static! {
-          static ref event_re: Regex = Regex::new(r"\[(?P<year>\d{4})-(?P<month>\d{2})-(?P<day>\d{2}) (?P<hour>\d{2}):(?P<minute>\d{2})\] (?P<content>.+)").unwrap();
-          static ref content_re: Regex = Regex::new(r"Guard #(?P<id>\d+) begins shift").unwrap();
+            static ref content_re: Regex = Regex::new(r"Guard #(?P<id>\d+) begins shift").unwrap();
         }
-        let event_cap = event_re.captures(s).unwrap();
-        let year = event_cap
-            .name("year")
-            .unwrap()
-            .as_str()
-            .parse::<i32>()
-            .unwrap();
-        let month = event_cap
-            .name("month")
-            .unwrap()
-            .as_str()
-            .parse::<i32>()
-            .unwrap();
-        let day = event_cap
-            .name("day")
-            .unwrap()
-            .as_str()
-            .parse::<i32>()
-            .unwrap();
-        let hour = event_cap
-            .name("hour")
-            .unwrap()
-            .as_str()
-            .parse::<i32>()
-            .unwrap();
-        let minute = event_cap
-            .name("minute")
-            .unwrap()
-            .as_str()
-            .parse::<i32>()
-            .unwrap();
-        let content = event_cap.name("content").unwrap().as_str();
+        let (timestamp, content) = LogTimestamp::parse_prefix(s)?;
+        let content = content.trim();
 
         let mut kind = EventType::BeganShift;
         let mut id = -1;
@@ -79,61 +84,76 @@ impl FromStr for Event {
                 kind = EventType::WokeUp;
             }
             _ => {
-                let content_cap = content_re.captures(content).unwrap();
-                id = content_cap
-                    .name("id")
-                    .unwrap()
-                    .as_str()
-                    .parse::<i32>()
-                    .unwrap();
+                let unknown = || ParseEventError::UnknownContent(String::from(content));
+                let content_cap = content_re.captures(content).ok_or_else(unknown)?;
+                id = content_cap["id"].parse::<i32>().map_err(|_| unknown())?;
             }
         };
 
         Ok(Event {
             id: id,
             kind: kind,
-            timestamp: format!("{}-{}-{}T{}:{}:00Z", year, month, day, hour, minute)
-                .parse::<DateTime<Utc>>()
-                .unwrap(),
+            timestamp,
         })
     }
 }
 
+// Parses every line of the log, replays the events in chronological order
+// and attributes each one to the guard whose shift it falls in
 #[aoc_generator(day4)]
 pub fn event_generator(input: &str) -> Vec<Event> {
     let mut cur_id = -1;
-    let mut lines: Vec<&str> = input.lines().collect();
-    lines.sort();
-    lines
-        .iter()
-        .map(|l| {
-            let mut e = l.parse::<Event>().unwrap();
-            if e.id == -1 {
-                e.id = cur_id;
-            } else {
-                cur_id = e.id;
-            }
-            e
-        })
-        .collect()
+    let mut events: Vec<Event> = input
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .map(|l| l.parse::<Event>().unwrap())
+        .collect();
+    // Stable, so events that are identical in time and kind keep their
+    // order from the log
+    events.sort_by(|a, b| {
+        a.timestamp
+            .cmp(&b.timestamp)
+            .then_with(|| a.kind.cmp(&b.kind))
+    });
+    for e in &mut events {
+        if e.id == -1 {
+            e.id = cur_id;
+        } else {
+            cur_id = e.id;
+        }
+    }
+    events
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct GuardState {
     guard_id: i32,
     asleep: bool,
-    timestamp: DateTime<Utc>,
+    timestamp: LogTimestamp,
 }
 
 impl GuardState {
-    fn from_event(ts: &DateTime<Utc>, event: &Event) -> Self {
+    pub fn guard_id(&self) -> i32 {
+        self.guard_id
+    }
+
+    pub fn asleep(&self) -> bool {
+        self.asleep
+    }
+
+    pub fn timestamp(&self) -> LogTimestamp {
+        self.timestamp
+    }
+
+    fn from_event(ts: &LogTimestamp, event: &Event) -> Self {
         GuardState {
             guard_id: event.id,
             asleep: match event.kind {
                 EventType::FellAsleep => true,
                 _ => false,
             },
-            timestamp: ts.clone(),
+            timestamp: *ts,
         }
     }
 }
@@ -145,7 +165,7 @@ pub fn generate_timeline(all_events: &[Event]) -> Vec<GuardState> {
         return vec![];
     }
     let mut events = all_events.to_vec();
-    let mut cursor = events[0].timestamp.clone();
+    let mut cursor = events[0].timestamp;
     let mut timeline = vec![];
     let mut guard_id = -1;
     let mut asleep = false;
@@ -161,103 +181,676 @@ pub fn generate_timeline(all_events: &[Event]) -> Vec<GuardState> {
             timeline.push(GuardState {
                 guard_id: guard_id,
                 asleep: asleep,
-                timestamp: cursor.clone(),
+                timestamp: cursor,
             });
         }
-        cursor = cursor + Duration::minutes(1);
+        cursor += 1;
     }
     timeline
 }
 
-#[aoc(day4, part1)]
-pub fn day4_part1(input: &[Event]) -> u32 {
-    let timeline = generate_timeline(input);
-
-    let mut guard_sleep_amounts: HashMap<i32, u32> = HashMap::new();
-    for state in &timeline {
-        if state.asleep {
-            // Tally this minute for the guard on duty then
-            if guard_sleep_amounts.contains_key(&state.guard_id) {
-                let c = guard_sleep_amounts[&state.guard_id];
-                guard_sleep_amounts.insert(state.guard_id, c + 1);
-            } else {
-                guard_sleep_amounts.insert(state.guard_id, 1);
+// Nap is a stretch of minutes a guard spent asleep, from the minute they fell
+// asleep up to but not including the minute they woke up
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Nap {
+    start: LogTimestamp,
+    end: LogTimestamp,
+}
+
+impl Nap {
+    pub fn start(&self) -> LogTimestamp {
+        self.start
+    }
+
+    pub fn end(&self) -> LogTimestamp {
+        self.end
+    }
+
+    // Gets how many minutes the nap lasted
+    pub fn minutes(&self) -> u32 {
+        (self.end - self.start) as u32
+    }
+}
+
+// Shift groups the naps a guard took between beginning their shift and the
+// next guard taking over
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Shift {
+    guard_id: i32,
+    start: LogTimestamp,
+    naps: Vec<Nap>,
+}
+
+impl Shift {
+    pub fn guard_id(&self) -> i32 {
+        self.guard_id
+    }
+
+    pub fn start(&self) -> LogTimestamp {
+        self.start
+    }
+
+    pub fn naps(&self) -> &[Nap] {
+        &self.naps
+    }
+}
+
+// Groups a chronologically-ordered event stream into shifts. Events before the
+// first shift have no guard to attribute them to and are skipped, and a nap
+// still open when the log ends is dropped since it has no known end.
+pub fn generate_shifts(events: &[Event]) -> Vec<Shift> {
+    let mut shifts: Vec<Shift> = vec![];
+    let mut asleep_since: Option<LogTimestamp> = None;
+    for event in events {
+        match event.kind {
+            EventType::BeganShift => {
+                // A guard relieved while asleep slept until the handover
+                if let (Some(start), Some(shift)) = (asleep_since.take(), shifts.last_mut()) {
+                    shift.naps.push(Nap {
+                        start,
+                        end: event.timestamp,
+                    });
+                }
+                shifts.push(Shift {
+                    guard_id: event.id,
+                    start: event.timestamp,
+                    naps: vec![],
+                });
+            }
+            EventType::FellAsleep => {
+                if !shifts.is_empty() && asleep_since.is_none() {
+                    asleep_since = Some(event.timestamp);
+                }
+            }
+            EventType::WokeUp => {
+                if let (Some(start), Some(shift)) = (asleep_since.take(), shifts.last_mut()) {
+                    shift.naps.push(Nap {
+                        start,
+                        end: event.timestamp,
+                    });
+                }
             }
         }
     }
+    shifts
+}
 
-    let mut guard_id: u32 = 0;
-    let mut highest = 0;
-    for pair in guard_sleep_amounts {
-        if pair.1 > highest {
-            guard_id = pair.0 as u32;
-            highest = pair.1;
+// MinuteBucket decides which minute a guard is tallied under for each minute
+// they spend asleep
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MinuteBucket {
+    // Only sleep during 00:00-00:59 counts, tallied by minute of the hour.
+    // This is the model the puzzle uses.
+    MidnightHour,
+    // All sleep counts, tallied by minute of the day (0-1439)
+    MinuteOfDay,
+    // All sleep counts, tallied by minutes elapsed since the shift began
+    SinceShiftStart,
+}
+
+impl MinuteBucket {
+    // Gets the bucket a minute asleep is tallied under, if it counts at all
+    pub fn bucket(self, shift_start: &LogTimestamp, minute: &LogTimestamp) -> Option<u32> {
+        match self {
+            MinuteBucket::MidnightHour => {
+                if minute.hour() == 0 {
+                    Some(u32::from(minute.minute()))
+                } else {
+                    None
+                }
+            }
+            MinuteBucket::MinuteOfDay => {
+                Some(u32::from(minute.hour()) * 60 + u32::from(minute.minute()))
+            }
+            MinuteBucket::SinceShiftStart => Some((*minute - *shift_start) as u32),
         }
     }
+}
+
+// SleepStats tallies how long each guard slept and at which minutes, under a
+// given MinuteBucket model. Tallies are kept in ascending order of guard id and
+// minute so that ties are always broken the same way.
+#[derive(Debug, Clone)]
+pub struct SleepStats {
+    bucket: MinuteBucket,
+    // guard_id, minutes asleep
+    guard_sleep_amounts: BTreeMap<i32, u32>,
+    // (guard_id, minute), asleep count
+    guard_sleep_records: BTreeMap<(i32, u32), u32>,
+    // guard_id, shifts worked
+    guard_shifts: BTreeMap<i32, u32>,
+    // guard_id, shifts with at least one nap
+    guard_restless_shifts: BTreeMap<i32, u32>,
+    // guard_id, minutes of the longest single nap
+    guard_longest_naps: BTreeMap<i32, u32>,
+}
+
+impl SleepStats {
+    pub fn new(events: &[Event], bucket: MinuteBucket) -> Self {
+        SleepStats::from_shifts(&generate_shifts(events), bucket)
+    }
 
-    let mut asleep_minutes: HashMap<u32, u32> = HashMap::new();
-    for state in timeline {
-        // Tally this minute as having had a guard asleep on duty
-        if state.asleep && state.guard_id == guard_id as i32 {
-            let minute = state.timestamp.time().minute();
-            if asleep_minutes.contains_key(&minute) {
-                let m = asleep_minutes[&minute];
-                asleep_minutes.insert(minute, m + 1);
-            } else {
-                asleep_minutes.insert(minute, 1);
+    pub fn from_shifts(shifts: &[Shift], bucket: MinuteBucket) -> Self {
+        let mut guard_sleep_amounts: BTreeMap<i32, u32> = BTreeMap::new();
+        let mut guard_sleep_records: BTreeMap<(i32, u32), u32> = BTreeMap::new();
+        let mut guard_shifts: BTreeMap<i32, u32> = BTreeMap::new();
+        let mut guard_restless_shifts: BTreeMap<i32, u32> = BTreeMap::new();
+        let mut guard_longest_naps: BTreeMap<i32, u32> = BTreeMap::new();
+        for shift in shifts {
+            // Guards who never slept are still listed, with nothing tallied
+            guard_sleep_amounts.entry(shift.guard_id).or_insert(0);
+            *guard_shifts.entry(shift.guard_id).or_insert(0) += 1;
+            if !shift.naps.is_empty() {
+                *guard_restless_shifts.entry(shift.guard_id).or_insert(0) += 1;
             }
+            for nap in &shift.naps {
+                let longest = guard_longest_naps.entry(shift.guard_id).or_insert(0);
+                *longest = u32::max(*longest, nap.minutes());
+                let mut cursor = nap.start;
+                while cursor < nap.end {
+                    if let Some(minute) = bucket.bucket(&shift.start, &cursor) {
+                        *guard_sleep_amounts.entry(shift.guard_id).or_insert(0) += 1;
+                        *guard_sleep_records
+                            .entry((shift.guard_id, minute))
+                            .or_insert(0) += 1;
+                    }
+                    cursor += 1;
+                }
+            }
+        }
+        SleepStats {
+            bucket,
+            guard_sleep_amounts,
+            guard_sleep_records,
+            guard_shifts,
+            guard_restless_shifts,
+            guard_longest_naps,
         }
     }
 
-    let mut minute: u32 = 0;
-    highest = 0;
-    for pair in asleep_minutes {
-        if pair.1 > highest {
-            minute = pair.0;
-            highest = pair.1;
-        }
+    pub fn bucket(&self) -> MinuteBucket {
+        self.bucket
+    }
+
+    // Gets the ids of every guard that worked a shift, in ascending order
+    pub fn guards(&self) -> Vec<i32> {
+        let mut guards: Vec<i32> = self.guard_sleep_amounts.keys().cloned().collect();
+        guards.sort();
+        guards
+    }
+
+    // Gets the total minutes the guard was asleep
+    pub fn minutes_asleep(&self, guard_id: i32) -> u32 {
+        *self.guard_sleep_amounts.get(&guard_id).unwrap_or(&0)
     }
 
-    guard_id * minute
+    // Gets how many times the guard was asleep at the given minute
+    pub fn times_asleep(&self, guard_id: i32, minute: u32) -> u32 {
+        *self
+            .guard_sleep_records
+            .get(&(guard_id, minute))
+            .unwrap_or(&0)
+    }
+
+    // Gets how many shifts the guard worked
+    pub fn shifts(&self, guard_id: i32) -> u32 {
+        *self.guard_shifts.get(&guard_id).unwrap_or(&0)
+    }
+
+    // Gets how many of the guard's shifts they fell asleep during
+    pub fn restless_shifts(&self, guard_id: i32) -> u32 {
+        *self.guard_restless_shifts.get(&guard_id).unwrap_or(&0)
+    }
+
+    // Gets the length in minutes of the guard's longest single nap
+    pub fn longest_nap(&self, guard_id: i32) -> u32 {
+        *self.guard_longest_naps.get(&guard_id).unwrap_or(&0)
+    }
+
+    // Gets every minute the guard was most often asleep at, in ascending order,
+    // along with how many times they were asleep then
+    pub fn sleepiest_minutes(&self, guard_id: i32) -> (Vec<u32>, u32) {
+        let records = self
+            .guard_sleep_records
+            .range((guard_id, 0)..=(guard_id, u32::MAX))
+            .map(|(&(_, minute), &count)| (minute, count));
+        top(records)
+    }
+
+    // Gets the earliest minute the guard was most often asleep at, along with
+    // how many times they were asleep then
+    pub fn sleepiest_minute(&self, guard_id: i32) -> Option<(u32, u32)> {
+        let (minutes, count) = self.sleepiest_minutes(guard_id);
+        minutes.first().map(|&minute| (minute, count))
+    }
+
+    // Reports the tallies for every guard, in ascending order of guard id
+    pub fn summary(&self) -> Vec<GuardSummary> {
+        self.guards()
+            .into_iter()
+            .map(|guard_id| GuardSummary {
+                guard_id,
+                minutes_asleep: self.minutes_asleep(guard_id),
+                sleepiest_minute: self.sleepiest_minute(guard_id),
+            })
+            .collect()
+    }
 }
 
-#[aoc(day4, part2)]
-pub fn day4_part2(input: &[Event]) -> u32 {
-    let timeline = generate_timeline(input);
+// GuardSummary reports how a single guard slept under a MinuteBucket model
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct GuardSummary {
+    pub guard_id: i32,
+    pub minutes_asleep: u32,
+    // (minute, times asleep) for the minute the guard slept through most
+    pub sleepiest_minute: Option<(u32, u32)>,
+}
 
-    // (guard_id, minute), asleep count
-    let mut guard_sleep_records: HashMap<(i32, u32), u32> = HashMap::new();
-    for state in &timeline {
-        let minute = state.timestamp.time().minute();
-        if state.asleep {
-            // Tally this minute for the guard on duty then
-            if guard_sleep_records.contains_key(&(state.guard_id, minute)) {
-                let c = guard_sleep_records[&(state.guard_id, minute)];
-                guard_sleep_records.insert((state.guard_id, minute), c + 1);
-            } else {
-                guard_sleep_records.insert((state.guard_id, minute), 1);
+// Reports per-guard sleep statistics for the event log under the given model
+pub fn guard_statistics(input: &[Event], bucket: MinuteBucket) -> Vec<GuardSummary> {
+    SleepStats::new(input, bucket).summary()
+}
+
+// Collects every item sharing the highest key, in the order given, along with
+// that key
+fn top<T, K, I>(items: I) -> (Vec<T>, K)
+where
+    K: Ord + Default,
+    I: Iterator<Item = (T, K)>,
+{
+    let mut best: Vec<T> = vec![];
+    let mut highest = K::default();
+    for (item, key) in items {
+        match key.cmp(&highest) {
+            Ordering::Greater => {
+                best = vec![item];
+                highest = key;
             }
+            Ordering::Equal if !best.is_empty() => best.push(item),
+            _ => {}
         }
     }
+    (best, highest)
+}
+
+// Fraction orders ratios by cross-multiplying rather than dividing, so equal
+// ratios compare as tied
+#[derive(Debug, Clone, Copy)]
+struct Fraction(u64, u64);
+
+impl Default for Fraction {
+    fn default() -> Fraction {
+        Fraction(0, 1)
+    }
+}
 
-    let mut guard_id: i32 = 0;
-    let mut minute: u32 = 0;
-    let mut highest: u32 = 0;
-    for (pair, v) in guard_sleep_records {
-        if v > highest {
-            guard_id = pair.0;
-            minute = pair.1;
-            highest = v;
-        }
+impl Ord for Fraction {
+    fn cmp(&self, other: &Fraction) -> Ordering {
+        (self.0 * other.1).cmp(&(other.0 * self.1))
+    }
+}
+
+impl PartialOrd for Fraction {
+    fn partial_cmp(&self, other: &Fraction) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Fraction {
+    fn eq(&self, other: &Fraction) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Fraction {}
+
+// Selection lists every (guard_id, minute) choice tied for a strategy's best
+// score, in ascending order of guard id and then minute
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Selection {
+    candidates: Vec<(i32, u32)>,
+}
+
+impl Selection {
+    pub fn candidates(&self) -> &[(i32, u32)] {
+        &self.candidates
     }
 
-    guard_id as u32 * minute
+    // Gets the choice with the lowest guard id, then the earliest minute
+    pub fn best(&self) -> Option<(i32, u32)> {
+        self.candidates.first().cloned()
+    }
+
+    // Gets whether more than one choice is tied for the best score, meaning
+    // the log does not settle the answer on its own
+    pub fn is_ambiguous(&self) -> bool {
+        self.candidates.len() > 1
+    }
+}
+
+// SleepStrategy picks a guard to sneak past, and the minute to do it at, from
+// the sleep statistics of every guard
+pub trait SleepStrategy {
+    // Lists every choice tied for the best score
+    fn select(&self, stats: &SleepStats) -> Selection;
+
+    // Picks the lowest guard id, then the earliest minute, among tied choices
+    fn choose(&self, stats: &SleepStats) -> Option<(i32, u32)> {
+        self.select(stats).best()
+    }
+}
+
+// Selects the guards with the highest score, each at every minute they were
+// most often asleep at
+fn select_by_guard<F>(stats: &SleepStats, score: F) -> Selection
+where
+    F: Fn(i32) -> u32,
+{
+    let guards = stats
+        .guards()
+        .into_iter()
+        .map(|guard_id| (guard_id, score(guard_id)));
+    let (guards, _) = top(guards);
+    let candidates = guards
+        .into_iter()
+        .flat_map(|guard_id| {
+            let (minutes, _) = stats.sleepiest_minutes(guard_id);
+            minutes.into_iter().map(move |minute| (guard_id, minute))
+        })
+        .collect();
+    Selection { candidates }
+}
+
+// Strategy 1: the guard with the most minutes asleep, at the minute they were
+// most often asleep
+pub struct MostMinutesAsleep;
+
+impl SleepStrategy for MostMinutesAsleep {
+    fn select(&self, stats: &SleepStats) -> Selection {
+        select_by_guard(stats, |guard_id| stats.minutes_asleep(guard_id))
+    }
+}
+
+// Strategy 2: the guard most frequently asleep on the same minute
+pub struct MostFrequentMinute;
+
+impl SleepStrategy for MostFrequentMinute {
+    fn select(&self, stats: &SleepStats) -> Selection {
+        let records = stats
+            .guard_sleep_records
+            .iter()
+            .map(|(&record, &count)| (record, count));
+        let (candidates, _) = top(records);
+        Selection { candidates }
+    }
+}
+
+// The guard who took the longest single nap, at the minute they were most
+// often asleep
+pub struct LongestNap;
+
+impl SleepStrategy for LongestNap {
+    fn select(&self, stats: &SleepStats) -> Selection {
+        select_by_guard(stats, |guard_id| stats.longest_nap(guard_id))
+    }
+}
+
+// The guard and minute with the highest fraction of that guard's shifts spent
+// asleep at that minute
+pub struct HighestShiftFraction;
+
+impl SleepStrategy for HighestShiftFraction {
+    fn select(&self, stats: &SleepStats) -> Selection {
+        let records = stats.guard_sleep_records.iter().map(|(&record, &count)| {
+            let shifts = stats.shifts(record.0);
+            (record, Fraction(u64::from(count), u64::from(shifts)))
+        });
+        let (candidates, _) = top(records);
+        Selection { candidates }
+    }
+}
+
+// The guard who fell asleep during the most shifts, at the minute they were
+// most often asleep
+pub struct MostShiftsAsleep;
+
+impl SleepStrategy for MostShiftsAsleep {
+    fn select(&self, stats: &SleepStats) -> Selection {
+        select_by_guard(stats, |guard_id| stats.restless_shifts(guard_id))
+    }
+}
+
+// Combines a strategy's choice into the puzzle answer
+fn answer(choice: Option<(i32, u32)>) -> u32 {
+    match choice {
+        Some((guard_id, minute)) => guard_id as u32 * minute,
+        None => 0,
+    }
+}
+
+#[aoc(day4, part1)]
+pub fn day4_part1(input: &[Event]) -> u32 {
+    let stats = SleepStats::new(input, MinuteBucket::MidnightHour);
+    answer(MostMinutesAsleep.choose(&stats))
+}
+
+#[aoc(day4, part2)]
+pub fn day4_part2(input: &[Event]) -> u32 {
+    let stats = SleepStats::new(input, MinuteBucket::MidnightHour);
+    answer(MostFrequentMinute.choose(&stats))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    // The example log from the puzzle, shared with the tests of submodules
+    pub const EXAMPLE: &str = "[1518-11-01 00:00] Guard #10 begins shift
+[1518-11-01 00:05] falls asleep
+[1518-11-01 00:25] wakes up
+[1518-11-01 00:30] falls asleep
+[1518-11-01 00:55] wakes up
+[1518-11-01 23:58] Guard #99 begins shift
+[1518-11-02 00:40] falls asleep
+[1518-11-02 00:50] wakes up
+[1518-11-03 00:05] Guard #10 begins shift
+[1518-11-03 00:24] falls asleep
+[1518-11-03 00:29] wakes up
+[1518-11-04 00:02] Guard #99 begins shift
+[1518-11-04 00:36] falls asleep
+[1518-11-04 00:46] wakes up
+[1518-11-05 00:03] Guard #99 begins shift
+[1518-11-05 00:45] falls asleep
+[1518-11-05 00:55] wakes up";
+
+    // Guard #7 dozes off before midnight and again after 1am
+    const OUTSIDE_MIDNIGHT: &str = "[1518-03-01 23:50] Guard #7 begins shift
+[1518-03-01 23:58] falls asleep
+[1518-03-02 00:02] wakes up
+[1518-03-02 01:10] falls asleep
+[1518-03-02 01:12] wakes up";
+
+    // #1 naps longest and most in total, #2 sleeps at 00:22 on every shift and
+    // #3 sleeps on the most shifts, always at 00:40
+    const TEAM_STRATEGIES: &str = "[1518-05-01 00:00] Guard #1 begins shift
+[1518-05-01 00:10] falls asleep
+[1518-05-01 00:50] wakes up
+[1518-05-02 00:00] Guard #1 begins shift
+[1518-05-02 00:15] falls asleep
+[1518-05-02 00:16] wakes up
+[1518-05-03 00:00] Guard #1 begins shift
+[1518-05-04 00:00] Guard #2 begins shift
+[1518-05-04 00:20] falls asleep
+[1518-05-04 00:25] wakes up
+[1518-05-05 00:00] Guard #2 begins shift
+[1518-05-05 00:22] falls asleep
+[1518-05-05 00:23] wakes up
+[1518-05-06 00:00] Guard #2 begins shift
+[1518-05-06 00:22] falls asleep
+[1518-05-06 00:24] wakes up
+[1518-05-07 00:00] Guard #3 begins shift
+[1518-05-07 00:40] falls asleep
+[1518-05-07 00:41] wakes up
+[1518-05-08 00:00] Guard #3 begins shift
+[1518-05-08 00:40] falls asleep
+[1518-05-08 00:41] wakes up
+[1518-05-09 00:00] Guard #3 begins shift
+[1518-05-09 00:40] falls asleep
+[1518-05-09 00:41] wakes up
+[1518-05-10 00:00] Guard #3 begins shift
+[1518-05-10 00:40] falls asleep
+[1518-05-10 00:41] wakes up
+[1518-05-11 00:00] Guard #3 begins shift";
+
+    #[test]
+    fn test_day4_part1() {
+        assert_eq!(day4_part1(&event_generator(EXAMPLE)), 240);
+    }
+
+    #[test]
+    fn test_day4_part2() {
+        assert_eq!(day4_part2(&event_generator(EXAMPLE)), 4455);
+    }
+
+    #[test]
+    fn test_puzzle_strategies() {
+        let stats = SleepStats::new(&event_generator(EXAMPLE), MinuteBucket::MidnightHour);
+        assert_eq!(MostMinutesAsleep.choose(&stats), Some((10, 24)));
+        assert_eq!(MostFrequentMinute.choose(&stats), Some((99, 45)));
+    }
+
+    #[test]
+    fn test_team_strategies() {
+        let stats = SleepStats::new(
+            &event_generator(TEAM_STRATEGIES),
+            MinuteBucket::MidnightHour,
+        );
+        assert_eq!(stats.shifts(3), 5);
+        assert_eq!(stats.restless_shifts(3), 4);
+        assert_eq!(stats.longest_nap(1), 40);
+        assert_eq!(MostMinutesAsleep.choose(&stats), Some((1, 15)));
+        assert_eq!(MostFrequentMinute.choose(&stats), Some((3, 40)));
+        assert_eq!(LongestNap.choose(&stats), Some((1, 15)));
+        assert_eq!(HighestShiftFraction.choose(&stats), Some((2, 22)));
+        assert_eq!(MostShiftsAsleep.choose(&stats), Some((3, 40)));
+    }
+
+    #[test]
+    fn test_selection_unambiguous() {
+        let stats = SleepStats::new(&event_generator(EXAMPLE), MinuteBucket::MidnightHour);
+        let selection = MostMinutesAsleep.select(&stats);
+        assert_eq!(selection.candidates(), &[(10, 24)]);
+        assert!(!selection.is_ambiguous());
+    }
+
+    #[test]
+    fn test_selection_ties() {
+        // #5 and #3 sleep through exactly the same minutes
+        let stats = SleepStats::new(
+            &event_generator(
+                "[1518-05-01 00:00] Guard #5 begins shift\n[1518-05-01 00:10] falls asleep\n[1518-05-01 00:12] wakes up\n[1518-05-02 00:00] Guard #3 begins shift\n[1518-05-02 00:10] falls asleep\n[1518-05-02 00:12] wakes up",
+            ),
+            MinuteBucket::MidnightHour,
+        );
+        assert_eq!(stats.sleepiest_minutes(5), (vec![10, 11], 1));
+        assert_eq!(stats.sleepiest_minute(5), Some((10, 1)));
+
+        let selection = MostMinutesAsleep.select(&stats);
+        assert_eq!(
+            selection.candidates(),
+            &[(3, 10), (3, 11), (5, 10), (5, 11)]
+        );
+        assert!(selection.is_ambiguous());
+        assert_eq!(selection.best(), Some((3, 10)));
+        assert_eq!(MostFrequentMinute.choose(&stats), Some((3, 10)));
+        assert_eq!(HighestShiftFraction.select(&stats), selection);
+    }
+
+    #[test]
+    fn test_strategies_without_sleep() {
+        let stats = SleepStats::new(
+            &event_generator("[1518-05-01 00:00] Guard #4 begins shift"),
+            MinuteBucket::MidnightHour,
+        );
+        assert_eq!(MostMinutesAsleep.choose(&stats), None);
+        assert_eq!(HighestShiftFraction.choose(&stats), None);
+    }
+
+    #[test]
+    fn test_generate_shifts() {
+        let shifts = generate_shifts(&event_generator(EXAMPLE));
+        assert_eq!(shifts.len(), 5);
+        assert_eq!(shifts[0].guard_id, 10);
+        assert_eq!(
+            shifts[0]
+                .naps
+                .iter()
+                .map(|n| n.minutes())
+                .collect::<Vec<_>>(),
+            vec![20, 25]
+        );
+        assert_eq!(shifts[1].guard_id, 99);
+        assert_eq!(shifts[1].naps.len(), 1);
+    }
+
+    #[test]
+    fn test_guard_statistics_midnight_hour() {
+        let stats = SleepStats::new(
+            &event_generator(OUTSIDE_MIDNIGHT),
+            MinuteBucket::MidnightHour,
+        );
+        assert_eq!(stats.minutes_asleep(7), 2);
+        assert_eq!(stats.times_asleep(7, 0), 1);
+        assert_eq!(stats.times_asleep(7, 1), 1);
+        assert_eq!(stats.times_asleep(7, 58), 0);
+        assert_eq!(stats.times_asleep(7, 10), 0);
+    }
+
+    #[test]
+    fn test_guard_statistics_report() {
+        assert_eq!(
+            guard_statistics(&event_generator(EXAMPLE), MinuteBucket::MidnightHour),
+            vec![
+                GuardSummary {
+                    guard_id: 10,
+                    minutes_asleep: 50,
+                    sleepiest_minute: Some((24, 2)),
+                },
+                GuardSummary {
+                    guard_id: 99,
+                    minutes_asleep: 30,
+                    sleepiest_minute: Some((45, 3)),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_guard_statistics_minute_of_day() {
+        let stats = SleepStats::new(
+            &event_generator(OUTSIDE_MIDNIGHT),
+            MinuteBucket::MinuteOfDay,
+        );
+        assert_eq!(stats.minutes_asleep(7), 6);
+        assert_eq!(stats.times_asleep(7, 23 * 60 + 58), 1);
+        assert_eq!(stats.times_asleep(7, 1), 1);
+        assert_eq!(stats.times_asleep(7, 70), 1);
+        assert_eq!(stats.times_asleep(7, 10), 0);
+    }
+
+    #[test]
+    fn test_guard_statistics_since_shift_start() {
+        let stats = SleepStats::new(
+            &event_generator(OUTSIDE_MIDNIGHT),
+            MinuteBucket::SinceShiftStart,
+        );
+        assert_eq!(stats.minutes_asleep(7), 6);
+        assert_eq!(stats.times_asleep(7, 8), 1);
+        assert_eq!(stats.times_asleep(7, 11), 1);
+        assert_eq!(stats.times_asleep(7, 80), 1);
+        assert_eq!(stats.times_asleep(7, 82), 0);
+    }
+
     #[test]
     fn test_event_from_str() {
         let events = event_generator("[1518-06-25 23:58] Guard #1069 begins shift");
@@ -267,7 +860,7 @@ mod tests {
             Event {
                 id: 1069,
                 kind: EventType::BeganShift,
-                timestamp: "1518-06-25T23:58:00Z".parse::<DateTime<Utc>>().unwrap(),
+                timestamp: "[1518-06-25 23:58]".parse::<LogTimestamp>().unwrap(),
             }
         );
     }
@@ -287,7 +880,7 @@ mod tests {
             Event {
                 id: 1237,
                 kind: EventType::BeganShift,
-                timestamp: "1518-11-22T23:54:00Z".parse::<DateTime<Utc>>().unwrap(),
+                timestamp: "[1518-11-22 23:54]".parse::<LogTimestamp>().unwrap(),
             }
         );
         assert_eq!(
@@ -295,7 +888,7 @@ mod tests {
             Event {
                 id: 1237,
                 kind: EventType::FellAsleep,
-                timestamp: "1518-11-23T00:04:00Z".parse::<DateTime<Utc>>().unwrap(),
+                timestamp: "[1518-11-23 00:04]".parse::<LogTimestamp>().unwrap(),
             }
         );
         assert_eq!(
@@ -303,11 +896,55 @@ mod tests {
             Event {
                 id: 1237,
                 kind: EventType::WokeUp,
-                timestamp: "1518-11-23T00:40:00Z".parse::<DateTime<Utc>>().unwrap(),
+                timestamp: "[1518-11-23 00:40]".parse::<LogTimestamp>().unwrap(),
             }
         );
     }
 
+    #[test]
+    fn test_events_sorted_by_parsed_timestamp() {
+        // Unpadded fields and extra whitespace would sort these lines out of
+        // chronological order if compared as raw text
+        let events = event_generator(
+            "  [1518-11-3 0:24]  falls asleep\n[1518-11-03 00:05] wakes up\n\n[1518-11-2 23:58] Guard #99 begins shift\n[1518-11-03 00:02]   falls asleep\n[1518-11-3 0:29] wakes up\n",
+        );
+        assert_eq!(
+            events
+                .iter()
+                .map(|e| (e.id, e.kind.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (99, EventType::BeganShift),
+                (99, EventType::FellAsleep),
+                (99, EventType::WokeUp),
+                (99, EventType::FellAsleep),
+                (99, EventType::WokeUp),
+            ]
+        );
+        assert_eq!(
+            events[3].timestamp,
+            "[1518-11-03 00:24]".parse::<LogTimestamp>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_events_same_minute_tie_break() {
+        let events = event_generator(
+            "[1518-11-01 00:00] wakes up\n[1518-11-01 00:00] falls asleep\n[1518-11-01 00:00] Guard #10 begins shift",
+        );
+        assert_eq!(
+            events
+                .iter()
+                .map(|e| (e.id, e.kind.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (10, EventType::BeganShift),
+                (10, EventType::FellAsleep),
+                (10, EventType::WokeUp),
+            ]
+        );
+    }
+
     #[test]
     fn test_timeline_from_str() {
         let events = event_generator("[1518-11-23 00:04] falls asleep\n[1518-11-22 23:54] Guard #1237 begins shift\n[1518-11-23 00:40] wakes up");
@@ -319,237 +956,237 @@ mod tests {
                 GuardState {
                     guard_id: 1237,
                     asleep: false,
-                    timestamp: "1518-11-22T23:54:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-22 23:54]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: false,
-                    timestamp: "1518-11-22T23:55:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-22 23:55]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: false,
-                    timestamp: "1518-11-22T23:56:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-22 23:56]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: false,
-                    timestamp: "1518-11-22T23:57:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-22 23:57]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: false,
-                    timestamp: "1518-11-22T23:58:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-22 23:58]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: false,
-                    timestamp: "1518-11-22T23:59:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-22 23:59]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: false,
-                    timestamp: "1518-11-23T00:00:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:00]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: false,
-                    timestamp: "1518-11-23T00:01:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:01]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: false,
-                    timestamp: "1518-11-23T00:02:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:02]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: false,
-                    timestamp: "1518-11-23T00:03:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:03]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:04:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:04]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:05:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:05]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:06:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:06]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:07:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:07]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:08:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:08]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:09:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:09]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:10:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:10]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:11:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:11]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:12:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:12]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:13:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:13]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:14:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:14]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:15:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:15]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:16:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:16]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:17:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:17]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:18:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:18]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:19:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:19]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:20:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:20]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:21:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:21]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:22:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:22]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:23:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:23]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:24:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:24]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:25:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:25]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:26:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:26]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:27:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:27]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:28:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:28]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:29:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:29]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:30:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:30]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:31:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:31]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:32:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:32]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:33:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:33]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:34:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:34]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:35:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:35]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:36:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:36]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:37:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:37]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:38:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:38]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: true,
-                    timestamp: "1518-11-23T00:39:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:39]".parse::<LogTimestamp>().unwrap()
                 },
                 GuardState {
                     guard_id: 1237,
                     asleep: false,
-                    timestamp: "1518-11-23T00:40:00Z".parse::<DateTime<Utc>>().unwrap()
+                    timestamp: "[1518-11-23 00:40]".parse::<LogTimestamp>().unwrap()
                 },
             ]
         );
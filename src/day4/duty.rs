@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+
+use super::generate_shifts;
+use super::timestamp::LogTimestamp;
+use super::Event;
+use super::GuardState;
+use super::Nap;
+use super::Shift;
+
+// GuardLog indexes the shifts in an event log so that questions about who was
+// on duty, and when they slept, can be answered without replaying the whole
+// timeline
+#[derive(Debug, Clone)]
+pub struct GuardLog {
+    // In chronological order
+    shifts: Vec<Shift>,
+    // When each shift ended: the next shift's start, or the minute after the
+    // last event for the final shift
+    ends: Vec<LogTimestamp>,
+    // guard_id, indices of the guard's shifts in chronological order
+    guard_shifts: BTreeMap<i32, Vec<usize>>,
+}
+
+impl GuardLog {
+    // Builds the index from a chronologically-ordered event stream
+    pub fn new(events: &[Event]) -> Self {
+        let shifts = generate_shifts(events);
+        let mut ends: Vec<LogTimestamp> = shifts.iter().skip(1).map(|s| s.start).collect();
+        if let Some(last) = events.last() {
+            if !shifts.is_empty() {
+                // The very last minute of the calendar can only end itself
+                ends.push(last.timestamp.checked_add(1).unwrap_or(last.timestamp));
+            }
+        }
+
+        let mut guard_shifts: BTreeMap<i32, Vec<usize>> = BTreeMap::new();
+        for (i, shift) in shifts.iter().enumerate() {
+            guard_shifts.entry(shift.guard_id).or_default().push(i);
+        }
+
+        GuardLog {
+            shifts,
+            ends,
+            guard_shifts,
+        }
+    }
+
+    pub fn shifts(&self) -> &[Shift] {
+        &self.shifts
+    }
+
+    // Finds the shift covering the given minute, if any
+    pub fn shift_at(&self, ts: &LogTimestamp) -> Option<&Shift> {
+        let i = self.shifts.partition_point(|s| s.start <= *ts);
+        if i == 0 || *ts >= self.ends[i - 1] {
+            return None;
+        }
+        Some(&self.shifts[i - 1])
+    }
+
+    // Gets the guard on duty at the given minute and whether they were asleep
+    pub fn on_duty_at(&self, ts: &LogTimestamp) -> Option<GuardState> {
+        let shift = self.shift_at(ts)?;
+        let i = shift.naps.partition_point(|n| n.start <= *ts);
+        Some(GuardState {
+            guard_id: shift.guard_id,
+            asleep: i > 0 && shift.naps[i - 1].end > *ts,
+            timestamp: *ts,
+        })
+    }
+
+    // Lists the guard's shifts that overlap the period from `from` up to but
+    // not including `to`, in chronological order
+    pub fn shifts_between(
+        &self,
+        guard_id: i32,
+        from: &LogTimestamp,
+        to: &LogTimestamp,
+    ) -> Vec<&Shift> {
+        let indices = match self.guard_shifts.get(&guard_id) {
+            Some(indices) => indices,
+            None => return vec![],
+        };
+        let first = indices.partition_point(|&i| self.ends[i] <= *from);
+        indices[first..]
+            .iter()
+            .take_while(|&&i| self.shifts[i].start < *to)
+            .map(|&i| &self.shifts[i])
+            .collect()
+    }
+
+    // Lists the naps the guard took that overlap the period from `from` up to
+    // but not including `to`, in chronological order. Naps are returned whole,
+    // even where they begin or end outside the period.
+    pub fn sleep_intervals(
+        &self,
+        guard_id: i32,
+        from: &LogTimestamp,
+        to: &LogTimestamp,
+    ) -> Vec<&Nap> {
+        self.shifts_between(guard_id, from, to)
+            .into_iter()
+            .flat_map(|shift| shift.naps.iter())
+            .filter(|nap| nap.end > *from && nap.start < *to)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::event_generator;
+    use super::super::tests::EXAMPLE;
+    use super::*;
+
+    fn ts(s: &str) -> LogTimestamp {
+        s.parse::<LogTimestamp>().unwrap()
+    }
+
+    #[test]
+    fn test_on_duty_at() {
+        let log = GuardLog::new(&event_generator(EXAMPLE));
+        let state = log.on_duty_at(&ts("[1518-11-03 00:24]")).unwrap();
+        assert_eq!(state.guard_id(), 10);
+        assert!(state.asleep());
+        assert_eq!(state.timestamp(), ts("[1518-11-03 00:24]"));
+
+        let state = log.on_duty_at(&ts("[1518-11-03 00:29]")).unwrap();
+        assert_eq!(state.guard_id(), 10);
+        assert!(!state.asleep());
+
+        let state = log.on_duty_at(&ts("[1518-11-01 23:59]")).unwrap();
+        assert_eq!(state.guard_id(), 99);
+        assert!(!state.asleep());
+    }
+
+    #[test]
+    fn test_on_duty_outside_log() {
+        let log = GuardLog::new(&event_generator(EXAMPLE));
+        assert_eq!(log.on_duty_at(&ts("[1518-10-31 23:59]")), None);
+        assert!(log.on_duty_at(&ts("[1518-11-05 00:55]")).is_some());
+        assert_eq!(log.on_duty_at(&ts("[1518-11-05 00:56]")), None);
+        assert_eq!(
+            GuardLog::new(&[]).on_duty_at(&ts("[1518-11-05 00:00]")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_on_duty_matches_timeline() {
+        let events = event_generator(EXAMPLE);
+        let log = GuardLog::new(&events);
+        for state in super::super::generate_timeline(&events) {
+            assert_eq!(log.on_duty_at(&state.timestamp), Some(state));
+        }
+    }
+
+    #[test]
+    fn test_sleep_intervals() {
+        let log = GuardLog::new(&event_generator(EXAMPLE));
+        let naps = log.sleep_intervals(10, &ts("[1518-11-01 00:00]"), &ts("[1518-11-04 00:00]"));
+        assert_eq!(
+            naps.iter().map(|n| n.minutes()).collect::<Vec<_>>(),
+            vec![20, 25, 5]
+        );
+
+        let naps = log.sleep_intervals(10, &ts("[1518-11-01 00:40]"), &ts("[1518-11-04 00:00]"));
+        assert_eq!(
+            naps.iter().map(|n| n.start()).collect::<Vec<_>>(),
+            vec![ts("[1518-11-01 00:30]"), ts("[1518-11-03 00:24]")]
+        );
+
+        let naps = log.sleep_intervals(99, &ts("[1518-11-04 00:40]"), &ts("[1518-11-04 00:41]"));
+        assert_eq!(naps.len(), 1);
+        assert_eq!(naps[0].end(), ts("[1518-11-04 00:46]"));
+
+        assert!(log
+            .sleep_intervals(99, &ts("[1518-11-04 00:46]"), &ts("[1518-11-05 00:45]"))
+            .is_empty());
+        assert!(log
+            .sleep_intervals(42, &ts("[1518-11-01 00:00]"), &ts("[1518-11-06 00:00]"))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_shifts_between() {
+        let log = GuardLog::new(&event_generator(EXAMPLE));
+        let shifts = log.shifts_between(99, &ts("[1518-11-02 00:00]"), &ts("[1518-11-05 00:00]"));
+        assert_eq!(
+            shifts.iter().map(|s| s.start()).collect::<Vec<_>>(),
+            vec![ts("[1518-11-01 23:58]"), ts("[1518-11-04 00:02]")]
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use super::Event;
+use super::EventType;
+use super::MinuteBucket;
+use super::Shift;
+use super::SleepStats;
+
+// Exporters for day4 data in CSV and JSON. Every format has a fixed set of
+// columns (or keys) in a fixed order, and timestamps are written as ISO-8601
+// in UTC, e.g. 1518-11-01T00:05:00Z.
+
+fn event_name(kind: &EventType) -> &'static str {
+    match kind {
+        EventType::BeganShift => "began_shift",
+        EventType::FellAsleep => "fell_asleep",
+        EventType::WokeUp => "woke_up",
+    }
+}
+
+fn bucket_name(bucket: MinuteBucket) -> &'static str {
+    match bucket {
+        MinuteBucket::MidnightHour => "midnight_hour",
+        MinuteBucket::MinuteOfDay => "minute_of_day",
+        MinuteBucket::SinceShiftStart => "since_shift_start",
+    }
+}
+
+fn csv(header: &str, rows: Vec<String>) -> String {
+    let mut out = String::from(header);
+    out.push('\n');
+    for row in rows {
+        out.push_str(&row);
+        out.push('\n');
+    }
+    out
+}
+
+// Every value written is either a number or a string made up of digits,
+// letters, '-', ':' and '_', so no escaping is needed
+fn json(objects: Vec<String>) -> String {
+    if objects.is_empty() {
+        return String::from("[]\n");
+    }
+    format!("[\n  {}\n]\n", objects.join(",\n  "))
+}
+
+// Columns: timestamp, guard_id, event
+pub fn events_csv(events: &[Event]) -> String {
+    csv(
+        "timestamp,guard_id,event",
+        events
+            .iter()
+            .map(|e| format!("{},{},{}", e.timestamp, e.id, event_name(&e.kind)))
+            .collect(),
+    )
+}
+
+// Keys: timestamp, guard_id, event
+pub fn events_json(events: &[Event]) -> String {
+    json(
+        events
+            .iter()
+            .map(|e| {
+                format!(
+                    "{{\"timestamp\": \"{}\", \"guard_id\": {}, \"event\": \"{}\"}}",
+                    e.timestamp,
+                    e.id,
+                    event_name(&e.kind)
+                )
+            })
+            .collect(),
+    )
+}
+
+// Columns: guard_id, shift_start, start, end, minutes. A nap ends at the
+// minute the guard woke up.
+pub fn naps_csv(shifts: &[Shift]) -> String {
+    csv(
+        "guard_id,shift_start,start,end,minutes",
+        shifts
+            .iter()
+            .flat_map(|shift| {
+                shift.naps.iter().map(move |nap| {
+                    format!(
+                        "{},{},{},{},{}",
+                        shift.guard_id,
+                        shift.start,
+                        nap.start,
+                        nap.end,
+                        nap.minutes()
+                    )
+                })
+            })
+            .collect(),
+    )
+}
+
+// Keys: guard_id, shift_start, start, end, minutes
+pub fn naps_json(shifts: &[Shift]) -> String {
+    json(
+        shifts
+            .iter()
+            .flat_map(|shift| {
+                shift.naps.iter().map(move |nap| {
+                    format!(
+                        "{{\"guard_id\": {}, \"shift_start\": \"{}\", \"start\": \"{}\", \"end\": \"{}\", \"minutes\": {}}}",
+                        shift.guard_id,
+                        shift.start,
+                        nap.start,
+                        nap.end,
+                        nap.minutes()
+                    )
+                })
+            })
+            .collect(),
+    )
+}
+
+// Columns: bucket, guard_id, minute, times_asleep. Rows are in ascending order
+// of guard id and then minute.
+pub fn tallies_csv(stats: &SleepStats) -> String {
+    csv(
+        "bucket,guard_id,minute,times_asleep",
+        stats
+            .guard_sleep_records
+            .iter()
+            .map(|(&(guard_id, minute), &count)| {
+                format!(
+                    "{},{},{},{}",
+                    bucket_name(stats.bucket),
+                    guard_id,
+                    minute,
+                    count
+                )
+            })
+            .collect(),
+    )
+}
+
+// Keys: bucket, guard_id, minute, times_asleep
+pub fn tallies_json(stats: &SleepStats) -> String {
+    json(
+        stats
+            .guard_sleep_records
+            .iter()
+            .map(|(&(guard_id, minute), &count)| {
+                format!(
+                    "{{\"bucket\": \"{}\", \"guard_id\": {}, \"minute\": {}, \"times_asleep\": {}}}",
+                    bucket_name(stats.bucket),
+                    guard_id,
+                    minute,
+                    count
+                )
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::event_generator;
+    use super::super::generate_shifts;
+    use super::*;
+
+    const LOG: &str = "[1518-11-01 00:00] Guard #10 begins shift
+[1518-11-01 00:05] falls asleep
+[1518-11-01 00:07] wakes up
+[1518-11-01 23:58] Guard #99 begins shift
+[1518-11-02 00:40] falls asleep
+[1518-11-02 00:41] wakes up";
+
+    #[test]
+    fn test_events_csv() {
+        assert_eq!(
+            events_csv(&event_generator(LOG)),
+            "timestamp,guard_id,event
+1518-11-01T00:00:00Z,10,began_shift
+1518-11-01T00:05:00Z,10,fell_asleep
+1518-11-01T00:07:00Z,10,woke_up
+1518-11-01T23:58:00Z,99,began_shift
+1518-11-02T00:40:00Z,99,fell_asleep
+1518-11-02T00:41:00Z,99,woke_up
+"
+        );
+    }
+
+    #[test]
+    fn test_events_json() {
+        assert_eq!(
+            events_json(&event_generator(LOG)[..2]),
+            "[
+  {\"timestamp\": \"1518-11-01T00:00:00Z\", \"guard_id\": 10, \"event\": \"began_shift\"},
+  {\"timestamp\": \"1518-11-01T00:05:00Z\", \"guard_id\": 10, \"event\": \"fell_asleep\"}
+]
+"
+        );
+        assert_eq!(events_json(&[]), "[]\n");
+    }
+
+    #[test]
+    fn test_naps_csv() {
+        assert_eq!(
+            naps_csv(&generate_shifts(&event_generator(LOG))),
+            "guard_id,shift_start,start,end,minutes
+10,1518-11-01T00:00:00Z,1518-11-01T00:05:00Z,1518-11-01T00:07:00Z,2
+99,1518-11-01T23:58:00Z,1518-11-02T00:40:00Z,1518-11-02T00:41:00Z,1
+"
+        );
+    }
+
+    #[test]
+    fn test_naps_json() {
+        assert_eq!(
+            naps_json(&generate_shifts(&event_generator(LOG))),
+            "[
+  {\"guard_id\": 10, \"shift_start\": \"1518-11-01T00:00:00Z\", \"start\": \"1518-11-01T00:05:00Z\", \"end\": \"1518-11-01T00:07:00Z\", \"minutes\": 2},
+  {\"guard_id\": 99, \"shift_start\": \"1518-11-01T23:58:00Z\", \"start\": \"1518-11-02T00:40:00Z\", \"end\": \"1518-11-02T00:41:00Z\", \"minutes\": 1}
+]
+"
+        );
+    }
+
+    #[test]
+    fn test_tallies_csv() {
+        let stats = SleepStats::new(&event_generator(LOG), MinuteBucket::MidnightHour);
+        assert_eq!(
+            tallies_csv(&stats),
+            "bucket,guard_id,minute,times_asleep
+midnight_hour,10,5,1
+midnight_hour,10,6,1
+midnight_hour,99,40,1
+"
+        );
+    }
+
+    #[test]
+    fn test_tallies_json() {
+        let stats = SleepStats::new(&event_generator(LOG), MinuteBucket::SinceShiftStart);
+        assert_eq!(
+            tallies_json(&stats),
+            "[
+  {\"bucket\": \"since_shift_start\", \"guard_id\": 10, \"minute\": 5, \"times_asleep\": 1},
+  {\"bucket\": \"since_shift_start\", \"guard_id\": 10, \"minute\": 6, \"times_asleep\": 1},
+  {\"bucket\": \"since_shift_start\", \"guard_id\": 99, \"minute\": 42, \"times_asleep\": 1}
+]
+"
+        );
+    }
+}
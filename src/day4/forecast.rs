@@ -0,0 +1,243 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use super::generate_shifts;
+use super::Event;
+use super::MinuteBucket;
+
+// z-score for a two-sided 95% confidence interval
+pub const Z_95: f64 = 1.959_964;
+
+// GuardForecast is one row of the probability table: how many shifts a guard
+// worked, and during how many of those they were asleep at each minute of the
+// midnight hour
+#[derive(Debug, PartialEq, Clone)]
+pub struct GuardForecast {
+    guard_id: i32,
+    shifts: u32,
+    asleep: [u32; 60],
+}
+
+impl GuardForecast {
+    pub fn guard_id(&self) -> i32 {
+        self.guard_id
+    }
+
+    pub fn shifts(&self) -> u32 {
+        self.shifts
+    }
+
+    // Gets how many shifts the guard was asleep at the given minute, or None
+    // if the minute isn't in the midnight hour (0 to 59)
+    pub fn times_asleep(&self, minute: u32) -> Option<u32> {
+        self.asleep.get(minute as usize).cloned()
+    }
+
+    // Gets the empirical probability that the guard is asleep at the given
+    // minute of the midnight hour, or None if the minute isn't in it
+    pub fn probability(&self, minute: u32) -> Option<f64> {
+        let times = self.times_asleep(minute)?;
+        if self.shifts == 0 {
+            return Some(0.0);
+        }
+        Some(f64::from(times) / f64::from(self.shifts))
+    }
+
+    // Gets the probability of being asleep at every minute of the midnight hour
+    pub fn probabilities(&self) -> Vec<f64> {
+        (0..60)
+            .filter_map(|minute| self.probability(minute))
+            .collect()
+    }
+
+    // Gets the Wilson score interval around the probability of being asleep
+    // at the given minute, for the z-score of the desired confidence (e.g.
+    // Z_95). Unlike the normal approximation it stays within [0, 1] and is
+    // still meaningful for guards seen on only a few shifts. None if the
+    // minute isn't in the midnight hour.
+    pub fn interval(&self, minute: u32, z: f64) -> Option<(f64, f64)> {
+        let p = self.probability(minute)?;
+        if self.shifts == 0 {
+            return Some((0.0, 1.0));
+        }
+        let n = f64::from(self.shifts);
+        let z2 = z * z;
+        let denominator = 1.0 + z2 / n;
+        let center = (p + z2 / (2.0 * n)) / denominator;
+        let half_width = z * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / denominator;
+        Some((
+            f64::max(0.0, center - half_width),
+            f64::min(1.0, center + half_width),
+        ))
+    }
+}
+
+// SleepForecast estimates, from the shifts in a log, how likely each guard is
+// to be asleep at each minute of the midnight hour
+#[derive(Debug, PartialEq, Clone)]
+pub struct SleepForecast {
+    // guard_id, forecast
+    guards: BTreeMap<i32, GuardForecast>,
+}
+
+impl SleepForecast {
+    pub fn new(events: &[Event]) -> Self {
+        let mut guards: BTreeMap<i32, GuardForecast> = BTreeMap::new();
+        for shift in generate_shifts(events) {
+            let forecast = guards.entry(shift.guard_id).or_insert(GuardForecast {
+                guard_id: shift.guard_id,
+                shifts: 0,
+                asleep: [0; 60],
+            });
+            forecast.shifts += 1;
+
+            // Count each minute at most once per shift
+            let mut asleep = [false; 60];
+            for nap in &shift.naps {
+                let mut cursor = nap.start;
+                while cursor < nap.end {
+                    if let Some(minute) = MinuteBucket::MidnightHour.bucket(&shift.start, &cursor) {
+                        asleep[minute as usize] = true;
+                    }
+                    cursor += 1;
+                }
+            }
+            for (minute, &a) in asleep.iter().enumerate() {
+                if a {
+                    forecast.asleep[minute] += 1;
+                }
+            }
+        }
+        SleepForecast { guards }
+    }
+
+    // Gets the forecast for every guard, in ascending order of guard id
+    pub fn guards(&self) -> Vec<&GuardForecast> {
+        self.guards.values().collect()
+    }
+
+    pub fn guard(&self, guard_id: i32) -> Option<&GuardForecast> {
+        self.guards.get(&guard_id)
+    }
+
+    // Gets the empirical probability that the guard is asleep at the given
+    // minute of the midnight hour, if the guard appears in the log and the
+    // minute is in the hour
+    pub fn probability(&self, guard_id: i32, minute: u32) -> Option<f64> {
+        self.guard(guard_id)?.probability(minute)
+    }
+
+    // Gets each guard's id, probability of being asleep at the minute and
+    // Wilson score interval, or nothing if the minute isn't in the midnight
+    // hour
+    fn candidates(&self, minute: u32) -> Vec<(i32, f64, (f64, f64))> {
+        self.guards
+            .values()
+            .filter_map(|g| {
+                Some((
+                    g.guard_id,
+                    g.probability(minute)?,
+                    g.interval(minute, Z_95)?,
+                ))
+            })
+            .collect()
+    }
+
+    // Finds the guard least likely to be asleep at the given minute, along
+    // with that probability. Between guards equally likely to be asleep, the
+    // one with the lower 95% upper bound (i.e. seen on more shifts) is
+    // preferred, then the lowest guard id. None if there are no guards or
+    // the minute isn't in the midnight hour.
+    pub fn least_likely_asleep(&self, minute: u32) -> Option<(i32, f64)> {
+        self.candidates(minute)
+            .into_iter()
+            .min_by(|a, b| {
+                a.1.partial_cmp(&b.1)
+                    .unwrap_or(Ordering::Equal)
+                    .then((a.2).1.partial_cmp(&(b.2).1).unwrap_or(Ordering::Equal))
+                    .then(a.0.cmp(&b.0))
+            })
+            .map(|(guard_id, p, _)| (guard_id, p))
+    }
+
+    // Finds the guard most likely to be asleep at the given minute, along with
+    // that probability, preferring the higher 95% lower bound on ties. None
+    // if there are no guards or the minute isn't in the midnight hour.
+    pub fn most_likely_asleep(&self, minute: u32) -> Option<(i32, f64)> {
+        self.candidates(minute)
+            .into_iter()
+            .min_by(|a, b| {
+                b.1.partial_cmp(&a.1)
+                    .unwrap_or(Ordering::Equal)
+                    .then((b.2).0.partial_cmp(&(a.2).0).unwrap_or(Ordering::Equal))
+                    .then(a.0.cmp(&b.0))
+            })
+            .map(|(guard_id, p, _)| (guard_id, p))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::event_generator;
+    use super::super::tests::EXAMPLE;
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-4,
+            "{} is not close to {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_probabilities() {
+        let forecast = SleepForecast::new(&event_generator(EXAMPLE));
+        assert_eq!(forecast.guards().len(), 2);
+        assert_eq!(forecast.guard(99).unwrap().shifts(), 3);
+        assert_eq!(forecast.probability(10, 24), Some(1.0));
+        assert_eq!(forecast.probability(10, 30), Some(0.5));
+        assert_eq!(forecast.probability(99, 45), Some(1.0));
+        assert_close(forecast.probability(99, 40).unwrap(), 2.0 / 3.0);
+        assert_eq!(forecast.probability(99, 0), Some(0.0));
+        assert_eq!(forecast.probability(42, 0), None);
+        assert_eq!(forecast.guard(10).unwrap().probabilities().len(), 60);
+        assert_eq!(forecast.probability(10, 60), None);
+        assert_eq!(forecast.guard(10).unwrap().times_asleep(60), None);
+        assert_eq!(forecast.guard(10).unwrap().times_asleep(24), Some(2));
+    }
+
+    #[test]
+    fn test_interval() {
+        let forecast = SleepForecast::new(&event_generator(EXAMPLE));
+        let (low, high) = forecast.guard(99).unwrap().interval(0, Z_95).unwrap();
+        assert_close(low, 0.0);
+        assert_close(high, 0.5615);
+
+        let (low, high) = forecast.guard(10).unwrap().interval(30, Z_95).unwrap();
+        assert_close(low, 0.0945);
+        assert_close(high, 0.9055);
+        assert_eq!(forecast.guard(10).unwrap().interval(75, Z_95), None);
+    }
+
+    #[test]
+    fn test_least_likely_asleep() {
+        let forecast = SleepForecast::new(&event_generator(EXAMPLE));
+        assert_eq!(forecast.least_likely_asleep(30), Some((99, 0.0)));
+        assert_eq!(forecast.least_likely_asleep(45), Some((10, 0.5)));
+        // Neither guard has slept at 00:00, but #99 has been seen more often
+        assert_eq!(forecast.least_likely_asleep(0), Some((99, 0.0)));
+        assert_eq!(SleepForecast::new(&[]).least_likely_asleep(30), None);
+        assert_eq!(forecast.least_likely_asleep(60), None);
+    }
+
+    #[test]
+    fn test_most_likely_asleep() {
+        let forecast = SleepForecast::new(&event_generator(EXAMPLE));
+        assert_eq!(forecast.most_likely_asleep(24), Some((10, 1.0)));
+        // #99 sleeps at this minute on every shift, #10 on only half of theirs
+        assert_eq!(forecast.most_likely_asleep(45), Some((99, 1.0)));
+        assert_eq!(forecast.most_likely_asleep(100), None);
+    }
+}
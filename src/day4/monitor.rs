@@ -0,0 +1,350 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use super::timestamp::LogTimestamp;
+use super::Event;
+use super::EventType;
+use super::MinuteBucket;
+use super::ParseEventError;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum MonitorError {
+    // The line could not be parsed as an event
+    Parse(ParseEventError),
+    // The event comes before the last event pushed in the order the batch
+    // replay uses: by timestamp, then shift changes before naps before
+    // wake-ups within a minute
+    OutOfOrder { last: Event, event: Event },
+}
+
+impl fmt::Display for MonitorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MonitorError::Parse(e) => write!(f, "unparseable event: {}", e),
+            MonitorError::OutOfOrder { last, event } => write!(
+                f,
+                "{:?} at {} arrived after {:?} at {}",
+                event.kind, event.timestamp, last.kind, last.timestamp
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MonitorError {}
+
+// Answers holds the current Strategy 1 and Strategy 2 choices as
+// (guard_id, minute)
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Answers {
+    pub strategy1: Option<(i32, u32)>,
+    pub strategy2: Option<(i32, u32)>,
+}
+
+// Whether a tally of `count` for `key` beats the current leader. Ties go to the
+// lower key, matching the batch strategies.
+fn beats<K: Ord>(key: &K, count: u32, leader: &Option<(K, u32)>) -> bool {
+    match leader {
+        None => true,
+        Some((k, c)) => count > *c || (count == *c && key < k),
+    }
+}
+
+// GuardMonitor keeps the Strategy 1 and Strategy 2 leaders up to date as
+// events arrive one at a time, e.g. while tailing a live log. Minutes are
+// tallied when the guard wakes up (or is relieved), so a nap in progress does
+// not count yet.
+#[derive(Debug, Clone)]
+pub struct GuardMonitor {
+    bucket: MinuteBucket,
+    last: Option<Event>,
+    // guard_id, shift start
+    on_duty: Option<(i32, LogTimestamp)>,
+    asleep_since: Option<LogTimestamp>,
+    // guard_id, minutes asleep
+    guard_sleep_amounts: BTreeMap<i32, u32>,
+    // (guard_id, minute), asleep count
+    guard_sleep_records: BTreeMap<(i32, u32), u32>,
+    // guard_id, (minute, asleep count) of the guard's sleepiest minute
+    sleepiest_minutes: BTreeMap<i32, (u32, u32)>,
+    // guard_id, minutes asleep of the guard asleep the longest
+    most_asleep: Option<(i32, u32)>,
+    // (guard_id, minute), asleep count of the most frequently asleep minute
+    most_frequent: Option<((i32, u32), u32)>,
+}
+
+impl Default for GuardMonitor {
+    fn default() -> Self {
+        GuardMonitor::new()
+    }
+}
+
+impl GuardMonitor {
+    // Tallies minutes the way the puzzle does, by minute of the midnight hour
+    pub fn new() -> Self {
+        GuardMonitor::with_bucket(MinuteBucket::MidnightHour)
+    }
+
+    pub fn with_bucket(bucket: MinuteBucket) -> Self {
+        GuardMonitor {
+            bucket,
+            last: None,
+            on_duty: None,
+            asleep_since: None,
+            guard_sleep_amounts: BTreeMap::new(),
+            guard_sleep_records: BTreeMap::new(),
+            sleepiest_minutes: BTreeMap::new(),
+            most_asleep: None,
+            most_frequent: None,
+        }
+    }
+
+    // Parses a single log line and pushes the event it describes
+    pub fn push_line(&mut self, line: &str) -> Result<(), MonitorError> {
+        let event = line.trim().parse::<Event>().map_err(MonitorError::Parse)?;
+        self.push(event)
+    }
+
+    // Records the next event. Events must arrive in the order event_generator
+    // replays them, by timestamp and then by kind within a minute, so live and
+    // batch runs agree; an event out of that order is rejected and leaves the
+    // tallies untouched. Naps before the first shift, with no guard to
+    // attribute them to, are ignored.
+    pub fn push(&mut self, event: Event) -> Result<(), MonitorError> {
+        if let Some(last) = &self.last {
+            if (event.timestamp, &event.kind) < (last.timestamp, &last.kind) {
+                return Err(MonitorError::OutOfOrder {
+                    last: last.clone(),
+                    event,
+                });
+            }
+        }
+        self.last = Some(event.clone());
+
+        match event.kind {
+            EventType::BeganShift => {
+                // A guard relieved while asleep slept until the handover
+                self.wake(event.timestamp);
+                self.on_duty = Some((event.id, event.timestamp));
+            }
+            EventType::FellAsleep => {
+                if self.on_duty.is_some() && self.asleep_since.is_none() {
+                    self.asleep_since = Some(event.timestamp);
+                }
+            }
+            EventType::WokeUp => self.wake(event.timestamp),
+        }
+        Ok(())
+    }
+
+    // Tallies the nap in progress, if any, as ending at the given minute
+    fn wake(&mut self, end: LogTimestamp) {
+        let asleep_since = self.asleep_since.take();
+        let (mut cursor, (guard_id, shift_start)) = match (asleep_since, self.on_duty) {
+            (Some(start), Some(on_duty)) => (start, on_duty),
+            _ => return,
+        };
+        while cursor < end {
+            if let Some(minute) = self.bucket.bucket(&shift_start, &cursor) {
+                self.tally(guard_id, minute);
+            }
+            cursor += 1;
+        }
+    }
+
+    fn tally(&mut self, guard_id: i32, minute: u32) {
+        let total = self.guard_sleep_amounts.entry(guard_id).or_insert(0);
+        *total += 1;
+        if beats(&guard_id, *total, &self.most_asleep) {
+            self.most_asleep = Some((guard_id, *total));
+        }
+
+        let count = self
+            .guard_sleep_records
+            .entry((guard_id, minute))
+            .or_insert(0);
+        *count += 1;
+        let sleepiest = self.sleepiest_minutes.get(&guard_id).cloned();
+        if beats(&minute, *count, &sleepiest) {
+            self.sleepiest_minutes.insert(guard_id, (minute, *count));
+        }
+        if beats(&(guard_id, minute), *count, &self.most_frequent) {
+            self.most_frequent = Some(((guard_id, minute), *count));
+        }
+    }
+
+    // Gets the current Strategy 1 and Strategy 2 choices
+    pub fn current_answers(&self) -> Answers {
+        Answers {
+            strategy1: self.most_asleep.and_then(|(guard_id, _)| {
+                self.sleepiest_minutes
+                    .get(&guard_id)
+                    .map(|&(minute, _)| (guard_id, minute))
+            }),
+            strategy2: self.most_frequent.map(|(record, _)| record),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::event_generator;
+    use super::super::tests::EXAMPLE;
+    use super::super::timestamp::ParseTimestampError;
+    use super::super::MostFrequentMinute;
+    use super::super::MostMinutesAsleep;
+    use super::super::SleepStats;
+    use super::super::SleepStrategy;
+    use super::*;
+
+    #[test]
+    fn test_monitor_example() {
+        let mut monitor = GuardMonitor::new();
+        for line in EXAMPLE.lines() {
+            monitor.push_line(line).unwrap();
+        }
+        assert_eq!(
+            monitor.current_answers(),
+            Answers {
+                strategy1: Some((10, 24)),
+                strategy2: Some((99, 45)),
+            }
+        );
+    }
+
+    #[test]
+    fn test_monitor_matches_batch() {
+        let events = event_generator(EXAMPLE);
+        let mut monitor = GuardMonitor::new();
+        for (i, event) in events.iter().enumerate() {
+            monitor.push(event.clone()).unwrap();
+            let stats = SleepStats::new(&events[..=i], MinuteBucket::MidnightHour);
+            assert_eq!(
+                monitor.current_answers(),
+                Answers {
+                    strategy1: MostMinutesAsleep.choose(&stats),
+                    strategy2: MostFrequentMinute.choose(&stats),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_monitor_empty() {
+        let mut monitor = GuardMonitor::new();
+        monitor
+            .push_line("[1518-11-01 00:05] falls asleep")
+            .unwrap();
+        monitor.push_line("[1518-11-01 00:25] wakes up").unwrap();
+        assert_eq!(
+            monitor.current_answers(),
+            Answers {
+                strategy1: None,
+                strategy2: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_monitor_out_of_order() {
+        let mut monitor = GuardMonitor::new();
+        monitor
+            .push_line("[1518-11-01 00:00] Guard #10 begins shift")
+            .unwrap();
+        monitor
+            .push_line("[1518-11-01 00:05] falls asleep")
+            .unwrap();
+        monitor.push_line("[1518-11-01 00:25] wakes up").unwrap();
+        let woke = "[1518-11-01 00:25] wakes up".parse::<Event>().unwrap();
+        let late = "[1518-11-01 00:20] falls asleep".parse::<Event>().unwrap();
+        assert_eq!(
+            monitor.push(late.clone()),
+            Err(MonitorError::OutOfOrder {
+                last: woke.clone(),
+                event: late,
+            })
+        );
+        // Within a minute, a guard falls asleep before waking up
+        let same_minute = "[1518-11-01 00:25] falls asleep".parse::<Event>().unwrap();
+        assert_eq!(
+            monitor.push(same_minute.clone()),
+            Err(MonitorError::OutOfOrder {
+                last: woke,
+                event: same_minute.clone(),
+            })
+        );
+        assert_eq!(
+            MonitorError::OutOfOrder {
+                last: "[1518-11-01 00:25] wakes up".parse().unwrap(),
+                event: same_minute,
+            }
+            .to_string(),
+            "FellAsleep at 1518-11-01T00:25:00Z arrived after WokeUp at 1518-11-01T00:25:00Z"
+        );
+        assert_eq!(monitor.current_answers().strategy1, Some((10, 5)));
+    }
+
+    #[test]
+    fn test_monitor_same_minute_order() {
+        // The log lists the wake-up first, but the batch replay puts the shift
+        // change before it, so the monitor must too
+        let log = "[1518-11-01 00:00] Guard #10 begins shift
+[1518-11-01 00:05] falls asleep
+[1518-11-01 00:30] wakes up
+[1518-11-01 00:30] Guard #99 begins shift";
+        let lines: Vec<&str> = log.lines().collect();
+        let mut monitor = GuardMonitor::new();
+        for line in &lines[..3] {
+            monitor.push_line(line).unwrap();
+        }
+        assert!(matches!(
+            monitor.push_line(lines[3]),
+            Err(MonitorError::OutOfOrder { .. })
+        ));
+
+        let events = event_generator(log);
+        let mut monitor = GuardMonitor::new();
+        for event in &events {
+            monitor.push(event.clone()).unwrap();
+        }
+        let stats = SleepStats::new(&events, MinuteBucket::MidnightHour);
+        assert_eq!(
+            monitor.current_answers(),
+            Answers {
+                strategy1: MostMinutesAsleep.choose(&stats),
+                strategy2: MostFrequentMinute.choose(&stats),
+            }
+        );
+    }
+
+    #[test]
+    fn test_monitor_parse_error() {
+        let mut monitor = GuardMonitor::new();
+        assert_eq!(
+            monitor.push_line("1518-11-01 00:00 Guard #10 begins shift"),
+            Err(MonitorError::Parse(ParseEventError::Timestamp(
+                ParseTimestampError::Malformed
+            )))
+        );
+        assert_eq!(
+            monitor.push_line("[1518-11-01 00:00] garbage"),
+            Err(MonitorError::Parse(ParseEventError::UnknownContent(
+                String::from("garbage")
+            )))
+        );
+        assert_eq!(
+            monitor.push_line("[1518-11-01 00:00] Guard #99999999999 begins shift"),
+            Err(MonitorError::Parse(ParseEventError::UnknownContent(
+                String::from("Guard #99999999999 begins shift")
+            )))
+        );
+        // Bad lines leave the monitor as it was
+        monitor
+            .push_line("[1518-11-01 00:00] Guard #10 begins shift")
+            .unwrap();
+        assert_eq!(
+            MonitorError::Parse(ParseEventError::UnknownContent(String::from("garbage")))
+                .to_string(),
+            "unparseable event: unknown event \"garbage\""
+        );
+    }
+}
@@ -0,0 +1,398 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::Add;
+use std::ops::AddAssign;
+use std::ops::Sub;
+use std::str::FromStr;
+
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Datelike, TimeZone, Timelike, Utc};
+
+// LogTimestamp is a minute-precision UTC timestamp as written in the guard log,
+// e.g. [1518-11-22 23:54]. Dates are proleptic Gregorian, and fields are
+// declared from most to least significant so timestamps order
+// chronologically.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct LogTimestamp {
+    year: i32,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ParseTimestampError {
+    // The text is not a bracketed "[year-month-day hour:minute]" prefix
+    Malformed,
+    // One of the fields is outside its calendar or clock range
+    OutOfRange,
+}
+
+impl fmt::Display for ParseTimestampError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseTimestampError::Malformed => write!(f, "malformed log timestamp"),
+            ParseTimestampError::OutOfRange => write!(f, "log timestamp out of range"),
+        }
+    }
+}
+
+impl std::error::Error for ParseTimestampError {}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Days since 1970-01-01, counting backwards for earlier dates. Shifting the
+// year to start in March puts the leap day last, so each month's offset into
+// the year follows a fixed pattern.
+fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_from_march = (i64::from(month) + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+// Inverse of days_from_civil
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u8;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    } as u8;
+    let year = year_of_era + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+// Parses the next run of digits, skipping any whitespace before it
+fn number(s: &str) -> Result<(i64, &str), ParseTimestampError> {
+    let s = s.trim_start();
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    match s[..end].parse::<i64>() {
+        Ok(n) => Ok((n, &s[end..])),
+        Err(_) => Err(ParseTimestampError::Malformed),
+    }
+}
+
+// Consumes the expected character, skipping any whitespace before it
+fn expect(s: &str, c: char) -> Result<&str, ParseTimestampError> {
+    let s = s.trim_start();
+    if s.starts_with(c) {
+        Ok(&s[c.len_utf8()..])
+    } else {
+        Err(ParseTimestampError::Malformed)
+    }
+}
+
+impl LogTimestamp {
+    pub fn new(
+        year: i32,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+    ) -> Result<Self, ParseTimestampError> {
+        if !(1..=12).contains(&month)
+            || day < 1
+            || day > days_in_month(i64::from(year), month)
+            || hour > 23
+            || minute > 59
+        {
+            return Err(ParseTimestampError::OutOfRange);
+        }
+        Ok(LogTimestamp {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+        })
+    }
+
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    // Parses the bracketed timestamp at the start of a log line, e.g.
+    // "[1518-11-22 23:54] falls asleep", returning it along with the rest of
+    // the line. Whitespace around the fields and unpadded fields are accepted.
+    pub fn parse_prefix(s: &str) -> Result<(Self, &str), ParseTimestampError> {
+        let s = expect(s, '[')?;
+        let (year, s) = number(s)?;
+        let s = expect(s, '-')?;
+        let (month, s) = number(s)?;
+        let s = expect(s, '-')?;
+        let (day, s) = number(s)?;
+        let (hour, s) = number(s)?;
+        let s = expect(s, ':')?;
+        let (minute, s) = number(s)?;
+        let s = expect(s, ']')?;
+
+        if year > i64::from(i32::MAX) || month > 12 || day > 31 || hour > 23 || minute > 59 {
+            return Err(ParseTimestampError::OutOfRange);
+        }
+        let timestamp = LogTimestamp::new(
+            year as i32,
+            month as u8,
+            day as u8,
+            hour as u8,
+            minute as u8,
+        )?;
+        Ok((timestamp, s))
+    }
+
+    // Minutes since 1970-01-01 00:00, negative for earlier timestamps
+    fn to_minutes(self) -> i64 {
+        let days = days_from_civil(i64::from(self.year), self.month, self.day);
+        days * 1440 + i64::from(self.hour) * 60 + i64::from(self.minute)
+    }
+
+    // Inverse of to_minutes, failing if the year doesn't fit in an i32
+    fn from_minutes(minutes: i64) -> Result<Self, ParseTimestampError> {
+        let (year, month, day) = civil_from_days(minutes.div_euclid(1440));
+        let minute_of_day = minutes.rem_euclid(1440);
+        Ok(LogTimestamp {
+            year: i32::try_from(year).map_err(|_| ParseTimestampError::OutOfRange)?,
+            month,
+            day,
+            hour: (minute_of_day / 60) as u8,
+            minute: (minute_of_day % 60) as u8,
+        })
+    }
+
+    // Moves the timestamp forward by a number of minutes, or None if that
+    // leaves the years an i32 holds
+    pub fn checked_add(self, minutes: i64) -> Option<Self> {
+        let minutes = self.to_minutes().checked_add(minutes)?;
+        LogTimestamp::from_minutes(minutes).ok()
+    }
+}
+
+impl FromStr for LogTimestamp {
+    type Err = ParseTimestampError;
+
+    // [1518-11-22 23:54]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (timestamp, rest) = LogTimestamp::parse_prefix(s)?;
+        if !rest.trim().is_empty() {
+            return Err(ParseTimestampError::Malformed);
+        }
+        Ok(timestamp)
+    }
+}
+
+// Formats as ISO-8601, e.g. 1518-11-22T23:54:00Z
+impl fmt::Display for LogTimestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:00Z",
+            self.year, self.month, self.day, self.hour, self.minute
+        )
+    }
+}
+
+// Moves the timestamp forward by a number of minutes. Like integer overflow,
+// leaving the years an i32 holds panics; see checked_add.
+impl Add<i64> for LogTimestamp {
+    type Output = LogTimestamp;
+
+    fn add(self, minutes: i64) -> LogTimestamp {
+        self.checked_add(minutes)
+            .expect("LogTimestamp addition overflowed")
+    }
+}
+
+impl AddAssign<i64> for LogTimestamp {
+    fn add_assign(&mut self, minutes: i64) {
+        *self = *self + minutes;
+    }
+}
+
+// Gets the number of minutes from the other timestamp to this one
+impl Sub for LogTimestamp {
+    type Output = i64;
+
+    fn sub(self, other: LogTimestamp) -> i64 {
+        self.to_minutes() - other.to_minutes()
+    }
+}
+
+// Fails with OutOfRange for years chrono can't represent, beyond about
+// 262,000 years either side of year 0
+#[cfg(feature = "chrono")]
+impl TryFrom<LogTimestamp> for DateTime<Utc> {
+    type Error = ParseTimestampError;
+
+    fn try_from(ts: LogTimestamp) -> Result<DateTime<Utc>, ParseTimestampError> {
+        Utc.with_ymd_and_hms(
+            ts.year,
+            u32::from(ts.month),
+            u32::from(ts.day),
+            u32::from(ts.hour),
+            u32::from(ts.minute),
+            0,
+        )
+        .single()
+        .ok_or(ParseTimestampError::OutOfRange)
+    }
+}
+
+// Truncates to the minute
+#[cfg(feature = "chrono")]
+impl From<DateTime<Utc>> for LogTimestamp {
+    fn from(dt: DateTime<Utc>) -> LogTimestamp {
+        LogTimestamp {
+            year: dt.year(),
+            month: dt.month() as u8,
+            day: dt.day() as u8,
+            hour: dt.hour() as u8,
+            minute: dt.minute() as u8,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ts(s: &str) -> LogTimestamp {
+        s.parse::<LogTimestamp>().unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            ts("[1518-11-22 23:54]"),
+            LogTimestamp::new(1518, 11, 22, 23, 54).unwrap()
+        );
+        assert_eq!(ts("[ 1518-3-2  0:05 ]"), ts("[1518-03-02 00:05]"));
+    }
+
+    #[test]
+    fn test_parse_prefix() {
+        let (timestamp, rest) =
+            LogTimestamp::parse_prefix("[1518-11-23 00:04] falls asleep").unwrap();
+        assert_eq!(timestamp, ts("[1518-11-23 00:04]"));
+        assert_eq!(rest, " falls asleep");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "1518-11-22 23:54".parse::<LogTimestamp>(),
+            Err(ParseTimestampError::Malformed)
+        );
+        assert_eq!(
+            "[1518-11-22 23:54] wakes up".parse::<LogTimestamp>(),
+            Err(ParseTimestampError::Malformed)
+        );
+        assert_eq!(
+            "[1518-02-29 00:00]".parse::<LogTimestamp>(),
+            Err(ParseTimestampError::OutOfRange)
+        );
+        assert_eq!(
+            "[1518-11-22 24:00]".parse::<LogTimestamp>(),
+            Err(ParseTimestampError::OutOfRange)
+        );
+        assert!("[1600-02-29 00:00]".parse::<LogTimestamp>().is_ok());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(ts("[1518-3-2 0:05]").to_string(), "1518-03-02T00:05:00Z");
+    }
+
+    #[test]
+    fn test_ordering() {
+        assert!(ts("[1518-11-22 23:54]") < ts("[1518-11-23 00:04]"));
+        assert!(ts("[1518-09-30 00:04]") < ts("[1518-10-01 00:00]"));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(ts("[1518-11-22 23:54]") + 10, ts("[1518-11-23 00:04]"));
+        assert_eq!(ts("[1518-12-31 23:59]") + 1, ts("[1519-01-01 00:00]"));
+        assert_eq!(ts("[1518-03-01 00:00]") + -1, ts("[1518-02-28 23:59]"));
+        assert_eq!(ts("[1600-03-01 00:00]") + -1, ts("[1600-02-29 23:59]"));
+        assert_eq!(ts("[1518-11-23 00:04]") - ts("[1518-11-22 23:54]"), 10);
+        assert_eq!(
+            ts("[1519-01-01 00:00]") - ts("[1518-01-01 00:00]"),
+            365 * 1440
+        );
+
+        let mut cursor = ts("[1518-11-22 23:59]");
+        cursor += 1;
+        assert_eq!(cursor, ts("[1518-11-23 00:00]"));
+    }
+
+    #[test]
+    fn test_unix_epoch() {
+        assert_eq!(ts("[1970-01-01 00:00]").to_minutes(), 0);
+        assert_eq!(LogTimestamp::from_minutes(0), Ok(ts("[1970-01-01 00:00]")));
+        assert_eq!(LogTimestamp::from_minutes(-1), Ok(ts("[1969-12-31 23:59]")));
+    }
+
+    #[test]
+    fn test_checked_add() {
+        let last = LogTimestamp::new(i32::MAX, 12, 31, 23, 59).unwrap();
+        assert_eq!(last.checked_add(0), Some(last));
+        assert_eq!(last.checked_add(1), None);
+        let first = LogTimestamp::new(i32::MIN, 1, 1, 0, 0).unwrap();
+        assert_eq!(first.checked_add(-1), None);
+        assert_eq!(last.checked_add(i64::MAX), None);
+        assert_eq!(
+            LogTimestamp::from_minutes(last.to_minutes() + 1),
+            Err(ParseTimestampError::OutOfRange)
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_chrono_conversion() {
+        let dt = DateTime::<Utc>::try_from(ts("[1518-11-22 23:54]")).unwrap();
+        assert_eq!(dt, "1518-11-22T23:54:00Z".parse::<DateTime<Utc>>().unwrap());
+        assert_eq!(LogTimestamp::from(dt), ts("[1518-11-22 23:54]"));
+        assert_eq!(
+            DateTime::<Utc>::try_from(LogTimestamp::new(i32::MAX, 1, 1, 0, 0).unwrap()),
+            Err(ParseTimestampError::OutOfRange)
+        );
+    }
+}
@@ -1,4 +1,15 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::thread;
+
+pub mod generator;
+pub mod index;
+pub mod polymer;
+pub mod removal;
+pub mod rules;
+
+use self::rules::CaseRules;
+use self::rules::ReactionRules;
 
 // Reacts on the given polymer chain and returns the most immediate result,
 // and whether a result was necessary
@@ -30,7 +41,35 @@ fn reacts(c1: &char, c2: &char) -> bool {
     c1 != c2 && c1.to_ascii_lowercase() == c2.to_ascii_lowercase()
 }
 
+// Fully reacts the given polymer in a single pass. Each unit either annihilates
+// the unit on top of the stack or is pushed onto it, so the stack always holds
+// the fully reacted prefix seen so far.
+pub fn react_with<R: ReactionRules>(rules: &R, polymer: &[R::Unit]) -> Vec<R::Unit> {
+    let mut stack: Vec<R::Unit> = Vec::with_capacity(polymer.len());
+    for unit in polymer {
+        if stack.last().is_some_and(|top| rules.reacts(top, unit)) {
+            stack.pop();
+        } else {
+            stack.push(unit.clone());
+        }
+    }
+    stack
+}
+
+// Fully reacts the given polymer under the puzzle's rules
+pub fn react(polymer: &[u8]) -> Vec<u8> {
+    react_with(&CaseRules, polymer)
+}
+
 pub fn polymer_reaction(input: &str) -> String {
+    // Only ASCII letters react, so removing them keeps the bytes valid UTF-8
+    String::from_utf8(react(input.as_bytes())).expect("reaction produced invalid UTF-8")
+}
+
+// Reacts the polymer one pair at a time with polymer_destruction. Quadratic,
+// but follows the puzzle's description step by step, so it's kept as a
+// reference for the stack reactor.
+pub fn polymer_reaction_stepwise(input: &str) -> String {
     let mut reaction = polymer_destruction(input);
     while reaction.1 {
         reaction = polymer_destruction(&reaction.0);
@@ -38,38 +77,219 @@ pub fn polymer_reaction(input: &str) -> String {
     reaction.0
 }
 
+// ReactionStep is one pair of units annihilating each other. Positions are
+// char offsets into the original input, and steps count from 1.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ReactionStep {
+    pub step: usize,
+    pub pair: (char, char),
+    pub position: (usize, usize),
+}
+
+struct ReactionSteps<'a> {
+    units: std::iter::Enumerate<std::str::Chars<'a>>,
+    // position, unit of the fully reacted prefix seen so far
+    stack: Vec<(usize, char)>,
+    step: usize,
+}
+
+impl<'a> Iterator for ReactionSteps<'a> {
+    type Item = ReactionStep;
+
+    fn next(&mut self) -> Option<ReactionStep> {
+        for (position, unit) in &mut self.units {
+            match self.stack.last() {
+                Some(&(top_position, top)) if reacts(&top, &unit) => {
+                    self.stack.pop();
+                    self.step += 1;
+                    return Some(ReactionStep {
+                        step: self.step,
+                        pair: (top, unit),
+                        position: (top_position, position),
+                    });
+                }
+                _ => self.stack.push((position, unit)),
+            }
+        }
+        None
+    }
+}
+
+// Lists the reactions in the order polymer_destruction performs them, always
+// removing the leftmost reacting pair. The stack holds no reacting pair, so the
+// leftmost one is always between its top and the next unit read.
+pub fn reaction_steps<'a>(input: &'a str) -> impl Iterator<Item = ReactionStep> + 'a {
+    ReactionSteps {
+        units: input.chars().enumerate(),
+        stack: Vec::new(),
+        step: 0,
+    }
+}
+
+// Lists the input followed by the polymer left after each reaction step
+pub fn reaction_polymers<'a>(input: &'a str) -> impl Iterator<Item = String> + 'a {
+    let units: Vec<char> = input.chars().collect();
+    let mut removed = vec![false; units.len()];
+    let render = move |removed: &[bool]| -> String {
+        units
+            .iter()
+            .zip(removed)
+            .filter(|&(_, &r)| !r)
+            .map(|(&c, _)| c)
+            .collect()
+    };
+    let first = render(&removed);
+    std::iter::once(first).chain(reaction_steps(input).map(move |step| {
+        removed[step.position.0] = true;
+        removed[step.position.1] = true;
+        render(&removed)
+    }))
+}
+
+// Prints every intermediate polymer of the reaction, one per line
+pub fn print_reaction(input: &str) {
+    for polymer in reaction_polymers(input) {
+        println!("{}", polymer);
+    }
+}
+
 #[aoc(day5, part1)]
 pub fn day5_part1(input: &str) -> usize {
     polymer_reaction(&input).len()
 }
 
+// Removes every unit of the given type from the polymer
+fn remove_kind<R: ReactionRules>(rules: &R, polymer: &[R::Unit], kind: &R::Kind) -> Vec<R::Unit> {
+    polymer
+        .iter()
+        .filter(|unit| rules.kind(unit).as_ref() != Some(kind))
+        .cloned()
+        .collect()
+}
+
+// UnitRemovalReport holds, for every unit type present in a polymer, the length
+// of the fully reacted polymer once all units of that type are removed. Types
+// are taken from the raw polymer, so a type that reacts away entirely is still
+// reported.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct UnitRemovalReport<K = char> {
+    // unit type, reacted length
+    lengths: BTreeMap<K, usize>,
+}
+
+impl UnitRemovalReport {
+    // Analyses the polymer under the puzzle's rules
+    pub fn new(polymer: &str) -> Self {
+        UnitRemovalReport::with_rules(&CaseRules, polymer)
+    }
+}
+
+impl<K: Ord + Clone> UnitRemovalReport<K> {
+    // Runs one trial per unit type. When the rules are kind-consistent,
+    // removing a type commutes with reacting, so the trials start from the
+    // reacted polymer. Otherwise a pair can react away in the raw input but
+    // not once one of its units is removed, so every trial starts from the raw
+    // input.
+    pub fn with_rules<R>(rules: &R, polymer: &str) -> Self
+    where
+        R: ReactionRules<Kind = K> + Sync,
+        R::Unit: Send + Sync,
+        K: Send,
+    {
+        let parsed = rules.parse(polymer);
+        if rules.kind_consistent() {
+            UnitRemovalReport::trials(rules, &parsed, &react_with(rules, &parsed))
+        } else {
+            UnitRemovalReport::trials(rules, &parsed, &parsed)
+        }
+    }
+
+    // Tries removing every type found in the raw polymer from `start`,
+    // splitting the types between a fixed number of worker threads, one per
+    // available core
+    fn trials<R>(rules: &R, raw: &[R::Unit], start: &[R::Unit]) -> Self
+    where
+        R: ReactionRules<Kind = K> + Sync,
+        R::Unit: Send + Sync,
+        K: Send,
+    {
+        let kinds: BTreeSet<K> = raw.iter().filter_map(|u| rules.kind(u)).collect();
+        let workers = thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(kinds.len())
+            .max(1);
+        let mut batches: Vec<Vec<K>> = (0..workers).map(|_| Vec::new()).collect();
+        for (i, kind) in kinds.into_iter().enumerate() {
+            batches[i % workers].push(kind);
+        }
+
+        let lengths = thread::scope(|scope| {
+            let trials: Vec<_> = batches
+                .into_iter()
+                .map(|batch| {
+                    scope.spawn(move || {
+                        batch
+                            .into_iter()
+                            .map(|kind| {
+                                let removed = remove_kind(rules, start, &kind);
+                                (kind, react_with(rules, &removed).len())
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            trials
+                .into_iter()
+                .flat_map(|trial| trial.join().expect("unit removal trial panicked"))
+                .collect()
+        });
+        UnitRemovalReport { lengths }
+    }
+
+    // Gets the reacted length for every unit type, in ascending order of type
+    pub fn lengths(&self) -> &BTreeMap<K, usize> {
+        &self.lengths
+    }
+
+    // Gets the reacted length once the given unit type is removed, if that type
+    // is present in the polymer
+    pub fn length_without(&self, kind: &K) -> Option<usize> {
+        self.lengths.get(kind).cloned()
+    }
+
+    // Finds the unit type whose removal leaves the shortest polymer, and that
+    // length. Ties go to the lowest type, e.g. the earliest letter.
+    pub fn best(&self) -> Option<(K, usize)> {
+        self.lengths
+            .iter()
+            .min_by(|a, b| a.1.cmp(b.1).then(a.0.cmp(b.0)))
+            .map(|(kind, &length)| (kind.clone(), length))
+    }
+}
+
+// Formats the answer as the removed unit type and the polymer left, e.g. c-daDA
 #[aoc(day5, part2)]
 pub fn day5_part2(input: &str) -> String {
-    let alphabet: Vec<char> = "abcdefghijklmnopqrstuvwxyz".chars().collect();
-    let mut results: HashMap<char, String> = HashMap::new();
-    for letter in alphabet {
-        let mut without_unit_type = String::from("");
-        for c in input.chars() {
-            if c.to_ascii_lowercase() != letter {
-                without_unit_type.push(c);
-            }
-        }
-        let reacted = polymer_reaction(&without_unit_type);
-        println!("removing unit type: {}, count: {}", letter, reacted.len());
-        results.insert(letter, reacted);
-    }
-    let mut best = ('_', String::from(""));
-    for (k, v) in results {
-        let l = best.1.len();
-        if l == 0 || v.len() <= l {
-            best = (k, v);
+    let reacted = react(input.as_bytes());
+    match UnitRemovalReport::trials(&CaseRules, input.as_bytes(), &reacted).best() {
+        Some((unit, _)) => {
+            let rest = react(&remove_kind(&CaseRules, &reacted, &unit));
+            format!("{}-{}", unit, String::from_utf8_lossy(&rest))
         }
+        None => String::from("_-"),
     }
-    format!("{}-{}", best.0, best.1)
+}
+
+#[aoc(day5, part2, length)]
+pub fn day5_part2_length(input: &str) -> usize {
+    UnitRemovalReport::new(input)
+        .best()
+        .map_or(0, |(_, length)| length)
 }
 
 #[cfg(test)]
 mod tests {
+    use super::generator::XorShift;
     use super::*;
 
     // dabAcCaCBAcCcaDA  The first 'cC' is removed.
@@ -110,6 +330,35 @@ mod tests {
         assert_eq!(polymer_reaction("dabAcCaCBAcCcaDA"), "dabCBAcaDA");
     }
 
+    #[test]
+    fn test_polymer_reaction_stepwise() {
+        assert_eq!(polymer_reaction_stepwise("dabAcCaCBAcCcaDA"), "dabCBAcaDA");
+    }
+
+    #[test]
+    fn test_react() {
+        assert_eq!(react(b"dabAcCaCBAcCcaDA"), b"dabCBAcaDA".to_vec());
+        assert_eq!(react(b"aA"), Vec::<u8>::new());
+        assert_eq!(react(b"abBA"), Vec::<u8>::new());
+        assert_eq!(react(b"abAB"), b"abAB".to_vec());
+        assert_eq!(react(b"aabAAB"), b"aabAAB".to_vec());
+        assert_eq!(react(b""), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_react_matches_stepwise() {
+        let mut rng = XorShift::new(0x2018_0005);
+        for i in 0..500 {
+            let polymer = rng.polymer(i % 60, 1 + i % 4);
+            assert_eq!(
+                polymer_reaction(&polymer),
+                polymer_reaction_stepwise(&polymer),
+                "polymer {}",
+                polymer
+            );
+        }
+    }
+
     #[test]
     fn test_day5_part1() {
         assert_eq!(day5_part1("dabAcCaCBAcCcaDA"), 10);
@@ -133,4 +382,133 @@ mod tests {
         // In this example, removing all C/c units was best, producing the answer 4.
         assert_eq!(day5_part2("dabAcCaCBAcCcaDA"), String::from("c-daDA"));
     }
+
+    #[test]
+    fn test_day5_part2_length() {
+        assert_eq!(day5_part2_length("dabAcCaCBAcCcaDA"), 4);
+        assert_eq!(day5_part2_length(""), 0);
+    }
+
+    #[test]
+    fn test_unit_removal_report() {
+        let report = UnitRemovalReport::new("dabAcCaCBAcCcaDA");
+        let expected: BTreeMap<char, usize> = [('a', 6), ('b', 8), ('c', 4), ('d', 6)]
+            .iter()
+            .cloned()
+            .collect();
+        assert_eq!(report.lengths(), &expected);
+        assert_eq!(report.length_without(&'c'), Some(4));
+        assert_eq!(report.length_without(&'e'), None);
+        assert_eq!(report.best(), Some(('c', 4)));
+    }
+
+    #[test]
+    fn test_unit_removal_report_ties() {
+        // Nothing is left once the polymer reacts, but both types were there
+        let report = UnitRemovalReport::new("baAB");
+        assert_eq!(report.length_without(&'a'), Some(0));
+        assert_eq!(report.length_without(&'b'), Some(0));
+        assert_eq!(report.best(), Some(('a', 0)));
+        assert_eq!(day5_part2("baAB"), "a-");
+        assert_eq!(day5_part2(""), "_-");
+
+        // Removing either type lets the other react away
+        let report = UnitRemovalReport::new("abAB");
+        assert_eq!(report.length_without(&'b'), Some(0));
+        assert_eq!(report.best(), Some(('a', 0)));
+    }
+
+    #[test]
+    fn test_unit_removal_matches_raw_input() {
+        let input = "dabAcCaCBAcCcaDA";
+        let report = UnitRemovalReport::new(input);
+        for (&unit, &length) in report.lengths() {
+            let raw = remove_kind(&CaseRules, input.as_bytes(), &unit);
+            assert_eq!(react(&raw).len(), length);
+        }
+    }
+
+    #[test]
+    fn test_unit_removal_with_table_rules() {
+        use super::rules::TableRules;
+
+        // The example polymer, spelled with element symbols instead of letters
+        let rules: TableRules = "Ag ag\nBe be\nCu cu\nDy dy".parse().unwrap();
+        let polymer = "dyagbeAgcuCuagCuBeAgcuCucuagDyAg";
+        assert_eq!(react_with(&rules, &rules.parse(polymer)).len(), 10);
+
+        let report = UnitRemovalReport::with_rules(&rules, polymer);
+        assert_eq!(report.lengths().len(), 4);
+        assert_eq!(report.length_without(&String::from("Ag")), Some(6));
+        assert_eq!(report.best(), Some((String::from("Cu"), 4)));
+    }
+
+    #[test]
+    fn test_unit_removal_inconsistent_kinds() {
+        use super::rules::TableRules;
+
+        // B reacts with both A and C, but has A's type, so "BCA" reacts down
+        // to "A" while removing C's type B leaves "BA", which reacts away
+        let rules: TableRules = "A B\nB C".parse().unwrap();
+        let report = UnitRemovalReport::with_rules(&rules, "BCA");
+        assert_eq!(report.length_without(&String::from("A")), Some(1));
+        assert_eq!(report.length_without(&String::from("B")), Some(0));
+        assert_eq!(report.best(), Some((String::from("B"), 0)));
+    }
+
+    #[test]
+    fn test_reaction_steps() {
+        let steps: Vec<ReactionStep> = reaction_steps("dabAcCaCBAcCcaDA").collect();
+        assert_eq!(
+            steps,
+            vec![
+                ReactionStep {
+                    step: 1,
+                    pair: ('c', 'C'),
+                    position: (4, 5),
+                },
+                ReactionStep {
+                    step: 2,
+                    pair: ('A', 'a'),
+                    position: (3, 6),
+                },
+                ReactionStep {
+                    step: 3,
+                    pair: ('c', 'C'),
+                    position: (10, 11),
+                },
+            ]
+        );
+        assert_eq!(reaction_steps("dabCBAcaDA").count(), 0);
+    }
+
+    #[test]
+    fn test_reaction_polymers() {
+        let polymers: Vec<String> = reaction_polymers("dabAcCaCBAcCcaDA").collect();
+        assert_eq!(
+            polymers,
+            vec![
+                "dabAcCaCBAcCcaDA",
+                "dabAaCBAcCcaDA",
+                "dabCBAcCcaDA",
+                "dabCBAcaDA"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reaction_polymers_match_destruction() {
+        let mut rng = XorShift::new(0x2018_0038);
+        for i in 0..200 {
+            let polymer = rng.polymer(i % 40, 1 + i % 3);
+            let mut expected = vec![polymer.clone()];
+            let mut reaction = polymer_destruction(&polymer);
+            while reaction.1 {
+                expected.push(reaction.0.clone());
+                reaction = polymer_destruction(&reaction.0);
+            }
+            let actual: Vec<String> = reaction_polymers(&polymer).collect();
+            assert_eq!(actual, expected, "polymer {}", polymer);
+        }
+    }
 }
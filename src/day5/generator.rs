@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+
+// xorshift64: small, seedable and good enough to shuffle test inputs. The
+// tests draw their random inputs from it too, so they're the same every run.
+pub(crate) struct XorShift(u64);
+
+impl XorShift {
+    pub(crate) fn new(seed: u64) -> Self {
+        // Zero is the one state xorshift never leaves
+        XorShift(if seed == 0 {
+            0x9E37_79B9_7F4A_7C15
+        } else {
+            seed
+        })
+    }
+
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    // Gets a number in [0, n)
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn letter(&mut self) -> u8 {
+        b'a' + self.below(26) as u8
+    }
+}
+
+#[cfg(test)]
+impl XorShift {
+    // Gets one of the first `types` unit types, in either polarity
+    pub(crate) fn unit(&mut self, types: usize) -> u8 {
+        let unit = b'a' + self.below(types) as u8;
+        if self.below(2) == 0 {
+            unit
+        } else {
+            unit.to_ascii_uppercase()
+        }
+    }
+
+    // Gets a polymer of `len` units of the first `types` types; few types make
+    // long chains of reactions likely
+    pub(crate) fn polymer(&mut self, len: usize, types: usize) -> String {
+        (0..len).map(|_| self.unit(types) as char).collect()
+    }
+}
+
+// GeneratedPolymer is a synthetic polymer along with the answers the day5
+// code should find for it
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct GeneratedPolymer {
+    pub polymer: String,
+    // the polymer once fully reacted
+    pub reacted: String,
+    // the unit type whose removal leaves the shortest polymer, and that
+    // length, or None if the polymer is empty
+    pub best_removal: Option<(char, usize)>,
+}
+
+impl GeneratedPolymer {
+    // Gets the expected day5_part2 answer, e.g. c-daDA
+    pub fn part2(&self) -> String {
+        match self.best_removal {
+            Some((unit, _)) => {
+                let rest: String = self.reacted.chars().filter(|&c| c != unit).collect();
+                format!("{}-{}", unit, rest)
+            }
+            None => String::from("_-"),
+        }
+    }
+}
+
+// Generates a polymer of `length` units that reacts down to `reacted_length`
+// units, from the given seed.
+//
+// The reacted polymer is a random lowercase core, which can't react. Reacting
+// pairs like "xX" or "Xx" are then inserted at random positions; each one
+// eventually annihilates, however the pairs nest, so the polymer reacts back to
+// the core. Removing a type from the polymer removes it from the core too, so
+// the best type to remove is the one most common in the core, the earliest
+// letter on ties. Types only in the inserted pairs appear in the core zero
+// times.
+pub fn generate(seed: u64, length: usize, reacted_length: usize) -> GeneratedPolymer {
+    assert!(
+        reacted_length <= length && (length - reacted_length).is_multiple_of(2),
+        "can't react {} units down to {}",
+        length,
+        reacted_length
+    );
+    let mut rng = XorShift::new(seed);
+
+    let core: Vec<u8> = (0..reacted_length).map(|_| rng.letter()).collect();
+    let mut polymer = core.clone();
+    for _ in 0..(length - reacted_length) / 2 {
+        let position = rng.below(polymer.len() + 1);
+        let unit = rng.letter();
+        let pair = if rng.below(2) == 0 {
+            [unit, unit.to_ascii_uppercase()]
+        } else {
+            [unit.to_ascii_uppercase(), unit]
+        };
+        polymer.splice(position..position, pair.iter().cloned());
+    }
+
+    let mut counts: BTreeMap<char, usize> = BTreeMap::new();
+    for &unit in &polymer {
+        counts.entry(unit.to_ascii_lowercase() as char).or_insert(0);
+    }
+    for &unit in &core {
+        *counts.entry(unit as char).or_insert(0) += 1;
+    }
+    let best_removal = counts
+        .iter()
+        .min_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)))
+        .map(|(&unit, &count)| (unit, reacted_length - count));
+
+    GeneratedPolymer {
+        polymer: String::from_utf8(polymer).unwrap(),
+        reacted: String::from_utf8(core).unwrap(),
+        best_removal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::day5_part1;
+    use super::super::day5_part2;
+    use super::super::day5_part2_length;
+    use super::super::polymer_reaction;
+    use super::super::polymer_reaction_stepwise;
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        let generated = generate(42, 100, 20);
+        assert_eq!(generated.polymer.len(), 100);
+        assert_eq!(generated.reacted.len(), 20);
+        assert_eq!(generate(42, 100, 20), generated);
+        assert_ne!(generate(43, 100, 20), generated);
+
+        let empty = generate(7, 10, 0);
+        assert_eq!(empty.reacted, "");
+        assert_eq!(polymer_reaction(&empty.polymer), "");
+        // Every type reacts away, so removing any leaves nothing
+        assert_eq!(empty.best_removal.map(|(_, length)| length), Some(0));
+        assert_eq!(day5_part2(&empty.polymer), empty.part2());
+        assert_eq!(generate(7, 0, 0).best_removal, None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_generate_parity() {
+        generate(1, 10, 3);
+    }
+
+    #[test]
+    fn test_generated_reaction() {
+        for seed in 0..50 {
+            let generated = generate(seed, 200 + 2 * seed as usize, 10 + 2 * seed as usize);
+            assert_eq!(polymer_reaction(&generated.polymer), generated.reacted);
+            assert_eq!(day5_part1(&generated.polymer), generated.reacted.len());
+        }
+        let generated = generate(5, 120, 30);
+        assert_eq!(
+            polymer_reaction_stepwise(&generated.polymer),
+            generated.reacted
+        );
+    }
+
+    #[test]
+    fn test_generated_part2() {
+        for seed in 0..50 {
+            let generated = generate(seed, 300, 40);
+            assert_eq!(day5_part2(&generated.polymer), generated.part2());
+            assert_eq!(
+                Some(day5_part2_length(&generated.polymer)),
+                generated.best_removal.map(|(_, length)| length)
+            );
+        }
+    }
+
+    #[test]
+    fn test_generated_large() {
+        let generated = generate(2018, 50_000, 10_000);
+        assert_eq!(polymer_reaction(&generated.polymer), generated.reacted);
+        assert_eq!(day5_part2(&generated.polymer), generated.part2());
+    }
+}
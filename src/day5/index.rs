@@ -0,0 +1,396 @@
+use std::ops::Range;
+
+// Hashes are polynomials in BASE modulo the Mersenne prime 2^61 - 1
+const MODULUS: u64 = (1 << 61) - 1;
+const BASE: u64 = 0x0005_DEEC_E66D;
+
+fn add(a: u64, b: u64) -> u64 {
+    (a + b) % MODULUS
+}
+
+fn sub(a: u64, b: u64) -> u64 {
+    (a + MODULUS - b) % MODULUS
+}
+
+fn mul(a: u64, b: u64) -> u64 {
+    (u128::from(a) * u128::from(b) % u128::from(MODULUS)) as u64
+}
+
+// Gets the hash of a single unit. A letter's inverse is the letter in the
+// other case; units that never react get inverses no unit hashes to.
+fn unit_hash(unit: u8, inverse: bool) -> u64 {
+    match (inverse, unit) {
+        (false, _) => u64::from(unit) + 1,
+        (true, b'a'..=b'z') => u64::from(unit.to_ascii_uppercase()) + 1,
+        (true, b'A'..=b'Z') => u64::from(unit.to_ascii_lowercase()) + 1,
+        (true, _) => u64::from(unit) + 257,
+    }
+}
+
+// Segment is a node of the segment tree: the reduced word of a run of units,
+// held implicitly as what is left of its two halves once the end of the left
+// half cancels against the start of the right one
+#[derive(Debug, Clone, Copy, Default)]
+struct Segment {
+    // length of the reduced word
+    len: usize,
+    // hash of the reduced word, and of its inverse
+    hash: u64,
+    inverse: u64,
+    // the two halves, or None for a single unit or an empty run
+    halves: Option<(usize, usize)>,
+    // units cancelled from the end of the left half, and from the start of
+    // the right half
+    cancelled: usize,
+    // hash of what is left of the left half
+    left_kept: u64,
+    // hash of the cancelled start of the right half, which is also the hash
+    // of the inverse of the cancelled end of the left half
+    right_cancelled: u64,
+    // hash of the inverse of what is left of the right half
+    right_kept_inverse: u64,
+}
+
+impl Segment {
+    fn unit(unit: u8) -> Self {
+        Segment {
+            len: 1,
+            hash: unit_hash(unit, false),
+            inverse: unit_hash(unit, true),
+            ..Segment::default()
+        }
+    }
+}
+
+// Segments reads the tree's segments, plus the temporary ones a query merges
+// together, which are numbered after the tree's
+struct Segments<'a> {
+    tree: &'a [Segment],
+    powers: &'a [u64],
+    merged: Vec<Segment>,
+}
+
+impl<'a> Segments<'a> {
+    fn new(tree: &'a [Segment], powers: &'a [u64]) -> Self {
+        Segments {
+            tree,
+            powers,
+            merged: Vec::new(),
+        }
+    }
+
+    fn get(&self, i: usize) -> &Segment {
+        match self.tree.get(i) {
+            Some(segment) => segment,
+            None => &self.merged[i - self.tree.len()],
+        }
+    }
+
+    // Keeps a temporary segment, returning its number
+    fn push(&mut self, segment: Segment) -> usize {
+        self.merged.push(segment);
+        self.tree.len() + self.merged.len() - 1
+    }
+
+    // Gets the hash of the first m units of segment i's reduced word, or of
+    // its inverse. Follows one half at each level, so takes O(depth).
+    //
+    // A segment's word is the left half's first `left kept` units followed by
+    // the right half's units from `cancelled` on, and its inverse is the
+    // inverse of the right half's kept units followed by the left half's
+    // inverse from `cancelled` on.
+    fn prefix(&self, i: usize, m: usize, inverse: bool) -> u64 {
+        let segment = self.get(i);
+        if m == 0 {
+            return 0;
+        }
+        if m == segment.len {
+            return if inverse {
+                segment.inverse
+            } else {
+                segment.hash
+            };
+        }
+        let (left, right) = segment.halves.expect("units have no proper prefixes");
+        let cancelled = segment.cancelled;
+        let (first, first_kept, second) = if inverse {
+            (right, segment.right_kept_inverse, left)
+        } else {
+            (left, segment.left_kept, right)
+        };
+        let kept = self.get(first).len - cancelled;
+        if m <= kept {
+            return self.prefix(first, m, inverse);
+        }
+        let rest = m - kept;
+        let tail = sub(
+            self.prefix(second, cancelled + rest, inverse),
+            mul(segment.right_cancelled, self.powers[rest]),
+        );
+        add(mul(first_kept, self.powers[rest]), tail)
+    }
+
+    // Reduces the concatenation of two segments' words. The end of the left
+    // word cancels against the start of the right word for as long as each
+    // unit is the inverse of its partner, and that holds for every shorter
+    // run once it holds for a longer one, so the run is found by binary search
+    // on the hashes.
+    fn merge(&self, left: usize, right: usize) -> Segment {
+        let (left_len, right_len) = (self.get(left).len, self.get(right).len);
+        let (mut low, mut high) = (0, left_len.min(right_len));
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+            if self.prefix(left, mid, true) == self.prefix(right, mid, false) {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+        let cancelled = low;
+        let (left_kept_len, right_kept_len) = (left_len - cancelled, right_len - cancelled);
+
+        let left_kept = self.prefix(left, left_kept_len, false);
+        let right_cancelled = self.prefix(right, cancelled, false);
+        let right_kept_inverse = self.prefix(right, right_kept_len, true);
+        let right_kept = sub(
+            self.get(right).hash,
+            mul(right_cancelled, self.powers[right_kept_len]),
+        );
+        let left_kept_inverse = sub(
+            self.get(left).inverse,
+            mul(right_cancelled, self.powers[left_kept_len]),
+        );
+        Segment {
+            len: left_kept_len + right_kept_len,
+            hash: add(mul(left_kept, self.powers[right_kept_len]), right_kept),
+            inverse: add(
+                mul(right_kept_inverse, self.powers[left_kept_len]),
+                left_kept_inverse,
+            ),
+            halves: Some((left, right)),
+            cancelled,
+            left_kept,
+            right_cancelled,
+            right_kept_inverse,
+        }
+    }
+}
+
+// PolymerIndex answers "how long is s[i..j) once fully reacted" for substrings
+// of one polymer under the puzzle's rules, while units are replaced.
+//
+// Reacting is reduction in a free group, where each letter's opposite case is
+// its inverse, and reduction is associative: the reduced word of a run is the
+// reduced words of its two halves with the end of one cancelled against the
+// start of the other. So a segment tree keeps the reduced word of each node's
+// run. A reduced word can be any irreducible word, e.g. "abAB", rather than
+// something of fixed size, so each node holds its word implicitly through its
+// halves, with hashes of the word and its inverse to find how far two words
+// cancel.
+//
+// Merging two nodes takes O(log n) hash comparisons of O(log n) each, so a
+// point update, which merges each of its O(log n) ancestors again, and a
+// query, which merges O(log n) nodes, each take O(log^3 n). Building takes
+// O(n). Cancellations are found by comparing 61-bit hashes, so a collision
+// could make one look longer than it is, though with odds of about n in 2^61.
+#[derive(Debug, Clone)]
+pub struct PolymerIndex {
+    units: Vec<u8>,
+    // number of leaves, a power of two
+    size: usize,
+    // the segment tree: node i has halves 2i and 2i + 1, and leaf k is node
+    // size + k, padded with empty runs
+    tree: Vec<Segment>,
+    // powers[k] is BASE^k, for every word length up to the polymer's
+    powers: Vec<u64>,
+}
+
+impl PolymerIndex {
+    pub fn new(polymer: &str) -> Self {
+        let units = polymer.as_bytes().to_vec();
+        let size = units.len().next_power_of_two();
+        let mut tree = vec![Segment::default(); 2 * size];
+        for (k, &unit) in units.iter().enumerate() {
+            tree[size + k] = Segment::unit(unit);
+        }
+        let mut powers = vec![1; units.len() + 1];
+        for k in 1..powers.len() {
+            powers[k] = mul(powers[k - 1], BASE);
+        }
+        let mut index = PolymerIndex {
+            units,
+            size,
+            tree,
+            powers,
+        };
+        for node in (1..size).rev() {
+            index.update(node);
+        }
+        index
+    }
+
+    pub fn len(&self) -> usize {
+        self.units.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.units.is_empty()
+    }
+
+    // Gets the length of the substring [range.start, range.end) once fully
+    // reacted. Panics if the range is out of bounds, as slicing would.
+    pub fn reduced_len(&self, range: Range<usize>) -> usize {
+        assert!(
+            range.start <= range.end && range.end <= self.units.len(),
+            "range {:?} out of bounds for polymer of length {}",
+            range,
+            self.units.len()
+        );
+        // The nodes covering the range, left to right
+        let (mut left, mut right) = (Vec::new(), Vec::new());
+        let (mut i, mut j) = (range.start + self.size, range.end + self.size);
+        while i < j {
+            if i % 2 == 1 {
+                left.push(i);
+                i += 1;
+            }
+            if j % 2 == 1 {
+                j -= 1;
+                right.push(j);
+            }
+            i /= 2;
+            j /= 2;
+        }
+        left.extend(right.into_iter().rev());
+
+        let mut segments = Segments::new(&self.tree, &self.powers);
+        let mut nodes = left.into_iter();
+        let mut reduced = match nodes.next() {
+            Some(node) => node,
+            None => return 0,
+        };
+        for node in nodes {
+            let merged = segments.merge(reduced, node);
+            reduced = segments.push(merged);
+        }
+        segments.get(reduced).len
+    }
+
+    // Replaces the unit at the given position, merging each node above it
+    // again
+    pub fn set(&mut self, position: usize, unit: u8) {
+        if self.units[position] == unit {
+            return;
+        }
+        self.units[position] = unit;
+        let mut node = self.size + position;
+        self.tree[node] = Segment::unit(unit);
+        while node > 1 {
+            node /= 2;
+            self.update(node);
+        }
+    }
+
+    fn update(&mut self, node: usize) {
+        let merged = Segments::new(&self.tree, &self.powers).merge(2 * node, 2 * node + 1);
+        self.tree[node] = merged;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::generator::XorShift;
+    use super::super::react;
+    use super::*;
+
+    #[test]
+    fn test_reduced_len() {
+        let polymer = "dabAcCaCBAcCcaDA";
+        let index = PolymerIndex::new(polymer);
+        assert_eq!(index.len(), 16);
+        assert_eq!(index.reduced_len(0..16), 10);
+        assert_eq!(index.reduced_len(4..6), 0);
+        assert_eq!(index.reduced_len(3..7), 0);
+        assert_eq!(index.reduced_len(2..8), 2);
+        assert_eq!(index.reduced_len(5..5), 0);
+        assert!(PolymerIndex::new("").is_empty());
+    }
+
+    #[test]
+    fn test_reduced_len_matches_react() {
+        let mut rng = XorShift::new(0x2018_0039);
+        for n in 0..40 {
+            let polymer: Vec<u8> = (0..n).map(|_| rng.unit(2)).collect();
+            let index = PolymerIndex::new(std::str::from_utf8(&polymer).unwrap());
+            for i in 0..=n {
+                for j in i..=n {
+                    assert_eq!(
+                        index.reduced_len(i..j),
+                        react(&polymer[i..j]).len(),
+                        "{:?} [{}, {})",
+                        String::from_utf8_lossy(&polymer),
+                        i,
+                        j
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_set() {
+        let mut rng = XorShift::new(0x2018_1039);
+        let mut polymer: Vec<u8> = (0..64).map(|_| rng.unit(3)).collect();
+        let mut index = PolymerIndex::new(std::str::from_utf8(&polymer).unwrap());
+        for _ in 0..300 {
+            let position = rng.below(64);
+            let unit = rng.unit(3);
+            polymer[position] = unit;
+            index.set(position, unit);
+
+            let i = rng.below(65);
+            let j = rng.below(65);
+            let (i, j) = (i.min(j), i.max(j));
+            assert_eq!(index.reduced_len(i..j), react(&polymer[i..j]).len());
+        }
+        assert_eq!(index.reduced_len(0..64), react(&polymer).len());
+    }
+
+    #[test]
+    fn test_set_large() {
+        // Deep nesting, so cancellations run across many levels of the tree
+        let mut rng = XorShift::new(0x2018_2039);
+        let mut polymer: Vec<u8> = (0..1000).map(|_| rng.unit(2)).collect();
+        let mut index = PolymerIndex::new(std::str::from_utf8(&polymer).unwrap());
+        for _ in 0..200 {
+            let position = rng.below(1000);
+            let unit = rng.unit(2);
+            polymer[position] = unit;
+            index.set(position, unit);
+
+            let i = rng.below(1001);
+            let j = rng.below(1001);
+            let (i, j) = (i.min(j), i.max(j));
+            assert_eq!(index.reduced_len(i..j), react(&polymer[i..j]).len());
+        }
+        assert_eq!(index.reduced_len(0..1000), react(&polymer).len());
+    }
+
+    #[test]
+    fn test_inert_units() {
+        // Units that aren't letters never react, not even with themselves
+        let mut index = PolymerIndex::new("a1A11aA");
+        assert_eq!(index.reduced_len(0..7), 5);
+        assert_eq!(index.reduced_len(3..5), 2);
+        index.set(1, b'b');
+        assert_eq!(index.reduced_len(0..3), 3);
+        index.set(1, b'A');
+        assert_eq!(index.reduced_len(0..3), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_reduced_len_out_of_bounds() {
+        PolymerIndex::new("aA").reduced_len(0..3);
+    }
+}
@@ -0,0 +1,168 @@
+use std::fmt;
+
+use im::Vector;
+
+use super::rules::CaseRules;
+use super::rules::ReactionRules;
+
+// Change records what one push or pop did to the polymer, so it can be undone
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Change {
+    // The unit was appended without reacting
+    Pushed(u8),
+    // The pushed unit annihilated the last unit, given here
+    Annihilated(u8),
+    // The last unit, given here, was removed
+    Popped(u8),
+}
+
+// Polymer is a fully reacted polymer that grows one unit at a time, e.g. as
+// units stream in from a sequencer. Both the units and the history are
+// persistent vectors, so cloning a Polymer is a cheap snapshot that shares
+// structure with the original.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Polymer {
+    units: Vector<u8>,
+    history: Vector<Change>,
+}
+
+impl Polymer {
+    pub fn new() -> Self {
+        Polymer::default()
+    }
+
+    // Appends a unit, reacting it with the last unit if they react
+    pub fn push(&mut self, unit: u8) -> Change {
+        let change = match self.units.back() {
+            Some(&last) if CaseRules.reacts(&last, &unit) => {
+                self.units.pop_back();
+                Change::Annihilated(last)
+            }
+            _ => {
+                self.units.push_back(unit);
+                Change::Pushed(unit)
+            }
+        };
+        self.history.push_back(change);
+        change
+    }
+
+    // Appends every unit of the given text in turn
+    pub fn push_str(&mut self, units: &str) {
+        for &unit in units.as_bytes() {
+            self.push(unit);
+        }
+    }
+
+    // Removes the last unit of the reacted polymer, if any
+    pub fn pop(&mut self) -> Option<u8> {
+        let unit = self.units.pop_back()?;
+        self.history.push_back(Change::Popped(unit));
+        Some(unit)
+    }
+
+    // Reverts the last push or pop, restoring any unit it annihilated or
+    // removed, and returns the change undone
+    pub fn undo(&mut self) -> Option<Change> {
+        let change = self.history.pop_back()?;
+        match change {
+            Change::Pushed(_) => {
+                self.units.pop_back();
+            }
+            Change::Annihilated(unit) | Change::Popped(unit) => self.units.push_back(unit),
+        }
+        Some(change)
+    }
+
+    pub fn len(&self) -> usize {
+        self.units.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.units.is_empty()
+    }
+
+    pub fn units(&self) -> &Vector<u8> {
+        &self.units
+    }
+
+    // Gets every push and pop so far, oldest first
+    pub fn history(&self) -> &Vector<Change> {
+        &self.history
+    }
+}
+
+impl fmt::Display for Polymer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for &unit in self.units.iter() {
+            write!(f, "{}", unit as char)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::polymer_reaction;
+    use super::*;
+
+    #[test]
+    fn test_push() {
+        let mut polymer = Polymer::new();
+        polymer.push_str("dabAcCaCBAcCcaDA");
+        assert_eq!(polymer.to_string(), "dabCBAcaDA");
+        assert_eq!(polymer.len(), 10);
+        assert_eq!(polymer.history().len(), 16);
+        assert_eq!(polymer.push(b'a'), Change::Annihilated(b'A'));
+        assert_eq!(polymer.push(b'a'), Change::Pushed(b'a'));
+    }
+
+    #[test]
+    fn test_push_matches_reaction() {
+        let input = "dabAcCaCBAcCcaDA";
+        let mut polymer = Polymer::new();
+        for (i, &unit) in input.as_bytes().iter().enumerate() {
+            polymer.push(unit);
+            assert_eq!(polymer.to_string(), polymer_reaction(&input[..=i]));
+        }
+    }
+
+    #[test]
+    fn test_undo() {
+        let input = "dabAcCaCBAcCcaDA";
+        let mut polymer = Polymer::new();
+        polymer.push_str(input);
+        for i in (0..input.len()).rev() {
+            polymer.undo().unwrap();
+            assert_eq!(polymer.to_string(), polymer_reaction(&input[..i]));
+        }
+        assert!(polymer.is_empty());
+        assert_eq!(polymer.undo(), None);
+    }
+
+    #[test]
+    fn test_pop() {
+        let mut polymer = Polymer::new();
+        polymer.push_str("abBc");
+        assert_eq!(polymer.pop(), Some(b'c'));
+        assert_eq!(polymer.to_string(), "a");
+        // The pushed unit reacts with what is left after the pop
+        assert_eq!(polymer.push(b'A'), Change::Annihilated(b'a'));
+        assert!(polymer.is_empty());
+        assert_eq!(polymer.pop(), None);
+
+        assert_eq!(polymer.undo(), Some(Change::Annihilated(b'a')));
+        assert_eq!(polymer.undo(), Some(Change::Popped(b'c')));
+        assert_eq!(polymer.to_string(), "ac");
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let mut polymer = Polymer::new();
+        polymer.push_str("dabAcC");
+        let snapshot = polymer.clone();
+        polymer.push_str("aCBAcCcaDA");
+        assert_eq!(snapshot.to_string(), "dabA");
+        assert_eq!(polymer.to_string(), "dabCBAcaDA");
+    }
+}
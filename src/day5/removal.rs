@@ -0,0 +1,241 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+use super::react;
+use super::remove_kind;
+use super::rules::CaseRules;
+use super::rules::ReactionRules;
+
+// RemovalSet is a set of unit types to remove from a polymer, and the length
+// of the polymer once they are removed and the rest fully reacts
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RemovalSet {
+    // unit types, in alphabetical order
+    pub kinds: Vec<char>,
+    pub length: usize,
+}
+
+impl RemovalSet {
+    // Orders removals from best to worst: shortest result first, then fewest
+    // types removed, then alphabetically
+    fn rank(&self, other: &RemovalSet) -> Ordering {
+        self.length
+            .cmp(&other.length)
+            .then(self.kinds.len().cmp(&other.kinds.len()))
+            .then(self.kinds.cmp(&other.kinds))
+    }
+}
+
+fn kinds_present(polymer: &[u8]) -> Vec<char> {
+    let kinds: BTreeSet<char> = polymer.iter().filter_map(|u| CaseRules.kind(u)).collect();
+    kinds.into_iter().collect()
+}
+
+fn consider(best: &mut Option<RemovalSet>, candidate: RemovalSet) {
+    if best
+        .as_ref()
+        .is_none_or(|b| candidate.rank(b) == Ordering::Less)
+    {
+        *best = Some(candidate);
+    }
+}
+
+// Gets every set of at most k of the types, each in the types' order
+fn combinations(kinds: &[char], k: usize) -> Vec<Vec<char>> {
+    let mut sets = vec![vec![]];
+    if k == 0 {
+        return sets;
+    }
+    for (i, &kind) in kinds.iter().enumerate() {
+        for mut rest in combinations(&kinds[i + 1..], k - 1) {
+            rest.insert(0, kind);
+            sets.push(rest);
+        }
+    }
+    sets
+}
+
+// Finds the set of at most k unit types whose removal leaves the shortest
+// polymer, by reacting the polymer without every such set. Only practical for
+// small k.
+pub fn best_removal_exhaustive(polymer: &str, k: usize) -> RemovalSet {
+    let reacted = react(polymer.as_bytes());
+    let kinds = kinds_present(&reacted);
+
+    let mut best = None;
+    for chosen in combinations(&kinds, k) {
+        let remaining: Vec<u8> = reacted
+            .iter()
+            .cloned()
+            .filter(|u| CaseRules.kind(u).is_none_or(|kind| !chosen.contains(&kind)))
+            .collect();
+        consider(
+            &mut best,
+            RemovalSet {
+                length: react(&remaining).len(),
+                kinds: chosen,
+            },
+        );
+    }
+    best.expect("the empty set is always a candidate")
+}
+
+// Lower bound on the length left once up to `budget` more of `candidates` are
+// removed from the reduced polymer. Reactions remove one unit of each case of a
+// type, so the difference between a type's lowercase and uppercase counts
+// never reacts away, and removing other types doesn't change it.
+fn lower_bound(polymer: &[u8], candidates: &[char], budget: usize) -> usize {
+    // type, (lowercase count, uppercase count)
+    let mut counts: BTreeMap<char, (usize, usize)> = BTreeMap::new();
+    let mut inert = 0;
+    for unit in polymer {
+        match CaseRules.kind(unit) {
+            Some(kind) => {
+                let count = counts.entry(kind).or_insert((0, 0));
+                if unit.is_ascii_lowercase() {
+                    count.0 += 1;
+                } else {
+                    count.1 += 1;
+                }
+            }
+            None => inert += 1,
+        }
+    }
+    let imbalance = |&(lower, upper): &(usize, usize)| lower.max(upper) - lower.min(upper);
+    let total: usize = counts.values().map(imbalance).sum();
+    let mut removable: Vec<usize> = candidates
+        .iter()
+        .filter_map(|kind| counts.get(kind).map(imbalance))
+        .collect();
+    removable.sort_unstable_by(|a, b| b.cmp(a));
+    inert + total - removable.iter().take(budget).sum::<usize>()
+}
+
+fn search(
+    polymer: &[u8],
+    kinds: &[char],
+    chosen: &mut Vec<char>,
+    budget: usize,
+    best: &mut Option<RemovalSet>,
+) {
+    consider(
+        best,
+        RemovalSet {
+            kinds: chosen.clone(),
+            length: polymer.len(),
+        },
+    );
+    if budget == 0 {
+        return;
+    }
+    for (i, &kind) in kinds.iter().enumerate() {
+        let rest = &kinds[i + 1..];
+        // Removing a type from the reduced polymer and reacting again gives
+        // the same result as removing it from the raw input
+        let reduced = react(&remove_kind(&CaseRules, polymer, &kind));
+        if let Some(b) = best {
+            if lower_bound(&reduced, rest, budget - 1) > b.length {
+                continue;
+            }
+        }
+        chosen.push(kind);
+        search(&reduced, rest, chosen, budget - 1, best);
+        chosen.pop();
+    }
+}
+
+// Finds the same set as best_removal_exhaustive with a depth-first search
+// that reuses each partially reduced polymer for every superset of its types,
+// and skips branches that can't beat the best set found so far
+pub fn best_removal(polymer: &str, k: usize) -> RemovalSet {
+    let reacted = react(polymer.as_bytes());
+    let kinds = kinds_present(&reacted);
+    let mut best = None;
+    search(&reacted, &kinds, &mut Vec::new(), k, &mut best);
+    best.expect("the empty set is always a candidate")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::generator::XorShift;
+    use super::*;
+
+    const EXAMPLE: &str = "dabAcCaCBAcCcaDA";
+
+    #[test]
+    fn test_best_removal_example() {
+        for &search in &[best_removal, best_removal_exhaustive] {
+            assert_eq!(
+                search(EXAMPLE, 0),
+                RemovalSet {
+                    kinds: vec![],
+                    length: 10,
+                }
+            );
+            assert_eq!(
+                search(EXAMPLE, 1),
+                RemovalSet {
+                    kinds: vec!['c'],
+                    length: 4,
+                }
+            );
+            assert_eq!(
+                search(EXAMPLE, 2),
+                RemovalSet {
+                    kinds: vec!['a', 'b'],
+                    length: 0,
+                }
+            );
+            // Removing more types than needed never helps
+            assert_eq!(search(EXAMPLE, 4).kinds, vec!['a', 'b']);
+        }
+    }
+
+    #[test]
+    fn test_combinations() {
+        assert_eq!(combinations(&['a', 'b', 'c'], 0), vec![vec![]]);
+        assert_eq!(
+            combinations(&['a', 'b', 'c'], 2),
+            vec![
+                vec![],
+                vec!['a'],
+                vec!['a', 'b'],
+                vec!['a', 'c'],
+                vec!['b'],
+                vec!['b', 'c'],
+                vec!['c'],
+            ]
+        );
+        // Only the 1 + 26 sets of at most one type, not all 2^26
+        let alphabet: Vec<char> = (b'a'..=b'z').map(char::from).collect();
+        assert_eq!(combinations(&alphabet, 1).len(), 27);
+        assert_eq!(combinations(&alphabet, 2).len(), 1 + 26 + 325);
+    }
+
+    #[test]
+    fn test_lower_bound() {
+        // Every type in "dabCBAcaDA" has as many lowercase as uppercase units
+        let polymer = react(EXAMPLE.as_bytes());
+        assert_eq!(lower_bound(&polymer, &[], 0), 0);
+        assert_eq!(lower_bound(b"aab\n", &['a'], 1), 2);
+        assert_eq!(lower_bound(b"aab\n", &['a', 'b'], 2), 1);
+    }
+
+    #[test]
+    fn test_best_removal_matches_exhaustive() {
+        let mut rng = XorShift::new(0x2018_0041);
+        for i in 0..100 {
+            let polymer = rng.polymer(10 + i % 50, 5);
+            for k in 0..4 {
+                assert_eq!(
+                    best_removal(&polymer, k),
+                    best_removal_exhaustive(&polymer, k),
+                    "polymer {}, k {}",
+                    polymer,
+                    k
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+// ReactionRules decides how a polymer splits into units, which adjacent units
+// annihilate each other, and which type each unit belongs to when removing a
+// whole type of unit
+pub trait ReactionRules {
+    type Unit: Clone + PartialEq;
+    type Kind: Ord + Clone;
+
+    // Splits a polymer written as text into its units
+    fn parse(&self, polymer: &str) -> Vec<Self::Unit>;
+
+    // Determines if the unit `a` followed by the unit `b` will react
+    fn reacts(&self, a: &Self::Unit, b: &Self::Unit) -> bool;
+
+    // Gets the type of the unit, or None for inert units that belong to no type
+    fn kind(&self, unit: &Self::Unit) -> Option<Self::Kind>;
+
+    // Whether the two units of every reacting pair share a type. If so,
+    // removing a type commutes with reacting, so removals can be tried on the
+    // reacted polymer instead of the raw one.
+    fn kind_consistent(&self) -> bool {
+        false
+    }
+}
+
+// CaseRules are the puzzle's rules: every byte is a unit, and the same letter
+// in opposite cases reacts. Letters belong to their lowercase type; anything
+// else is inert.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct CaseRules;
+
+impl ReactionRules for CaseRules {
+    type Unit = u8;
+    type Kind = char;
+
+    fn parse(&self, polymer: &str) -> Vec<u8> {
+        polymer.as_bytes().to_vec()
+    }
+
+    fn reacts(&self, a: &u8, b: &u8) -> bool {
+        a != b && a.eq_ignore_ascii_case(b)
+    }
+
+    fn kind(&self, unit: &u8) -> Option<char> {
+        if unit.is_ascii_alphabetic() {
+            Some(unit.to_ascii_lowercase() as char)
+        } else {
+            None
+        }
+    }
+
+    fn kind_consistent(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ParseRulesError {
+    // The rule on this line (counting from 1) names only one unit
+    MissingPartner(usize),
+    // The rule on this line (counting from 1) has more than three fields
+    TooManyFields(usize),
+}
+
+impl fmt::Display for ParseRulesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseRulesError::MissingPartner(line) => {
+                write!(f, "rule on line {} names only one unit", line)
+            }
+            ParseRulesError::TooManyFields(line) => {
+                write!(f, "rule on line {} has too many fields", line)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseRulesError {}
+
+// TableRules are read from a text table with one rule per line:
+//
+//   # comment
+//   A B        A and B react with each other, and share the type "A"
+//   Fe Ox rust Fe and Ox react with each other, and share the type "rust"
+//
+// Units are tokens of one or more characters. A unit keeps the type given on
+// the first line it appears on. Polymers are split greedily into the longest
+// known tokens; whitespace is skipped and any other unknown character is an
+// inert unit of its own.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct TableRules {
+    // unit, type
+    kinds: BTreeMap<String, String>,
+    // unit, units it reacts with
+    partners: BTreeMap<String, BTreeSet<String>>,
+    // known units, longest first
+    tokens: Vec<String>,
+}
+
+impl TableRules {
+    // Gets the units named by the table, longest first
+    pub fn tokens(&self) -> &[String] {
+        &self.tokens
+    }
+}
+
+impl FromStr for TableRules {
+    type Err = ParseRulesError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rules = TableRules::default();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let (a, b, kind) = match fields.len() {
+                1 => return Err(ParseRulesError::MissingPartner(i + 1)),
+                2 => (fields[0], fields[1], fields[0]),
+                3 => (fields[0], fields[1], fields[2]),
+                _ => return Err(ParseRulesError::TooManyFields(i + 1)),
+            };
+            for &unit in &[a, b] {
+                rules
+                    .kinds
+                    .entry(String::from(unit))
+                    .or_insert_with(|| String::from(kind));
+            }
+            rules
+                .partners
+                .entry(String::from(a))
+                .or_default()
+                .insert(String::from(b));
+            rules
+                .partners
+                .entry(String::from(b))
+                .or_default()
+                .insert(String::from(a));
+        }
+        rules.tokens = rules.kinds.keys().cloned().collect();
+        rules
+            .tokens
+            .sort_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+        Ok(rules)
+    }
+}
+
+impl ReactionRules for TableRules {
+    type Unit = String;
+    type Kind = String;
+
+    fn parse(&self, polymer: &str) -> Vec<String> {
+        let mut units = Vec::new();
+        let mut rest = polymer;
+        while let Some(c) = rest.chars().next() {
+            if c.is_whitespace() {
+                rest = &rest[c.len_utf8()..];
+                continue;
+            }
+            let token = match self.tokens.iter().find(|t| rest.starts_with(t.as_str())) {
+                Some(token) => token.clone(),
+                None => c.to_string(),
+            };
+            rest = &rest[token.len()..];
+            units.push(token);
+        }
+        units
+    }
+
+    fn reacts(&self, a: &String, b: &String) -> bool {
+        self.partners.get(a).is_some_and(|p| p.contains(b))
+    }
+
+    fn kind(&self, unit: &String) -> Option<String> {
+        self.kinds.get(unit).cloned()
+    }
+
+    fn kind_consistent(&self) -> bool {
+        self.partners
+            .iter()
+            .all(|(a, partners)| partners.iter().all(|b| self.kinds[a] == self.kinds[b]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLE: &str = "# metals and their oxides
+Fe Ox rust
+F f
+
+x y";
+
+    #[test]
+    fn test_case_rules() {
+        assert!(CaseRules.reacts(&b'a', &b'A'));
+        assert!(!CaseRules.reacts(&b'a', &b'a'));
+        assert!(!CaseRules.reacts(&b'a', &b'B'));
+        assert_eq!(CaseRules.kind(&b'Q'), Some('q'));
+        assert_eq!(CaseRules.kind(&b'\n'), None);
+    }
+
+    #[test]
+    fn test_table_rules_parse() {
+        let rules: TableRules = TABLE.parse().unwrap();
+        assert_eq!(rules.tokens(), &["Fe", "Ox", "F", "f", "x", "y"]);
+        assert_eq!(rules.parse("FeOx F\tfz"), vec!["Fe", "Ox", "F", "f", "z"]);
+        assert!(rules.reacts(&String::from("Ox"), &String::from("Fe")));
+        assert!(!rules.reacts(&String::from("Fe"), &String::from("f")));
+        assert_eq!(rules.kind(&String::from("Ox")), Some(String::from("rust")));
+        assert_eq!(rules.kind(&String::from("f")), Some(String::from("F")));
+        assert_eq!(rules.kind(&String::from("z")), None);
+    }
+
+    #[test]
+    fn test_kind_consistent() {
+        assert!(CaseRules.kind_consistent());
+        assert!(TABLE.parse::<TableRules>().unwrap().kind_consistent());
+        // B keeps the type A from the first line, so B and C differ
+        assert!(!"A B\nB C".parse::<TableRules>().unwrap().kind_consistent());
+    }
+
+    #[test]
+    fn test_table_rules_errors() {
+        assert_eq!(
+            "a b\nc\n".parse::<TableRules>(),
+            Err(ParseRulesError::MissingPartner(2))
+        );
+        assert_eq!(
+            "a b c d".parse::<TableRules>(),
+            Err(ParseRulesError::TooManyFields(1))
+        );
+    }
+}
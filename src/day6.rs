@@ -1,32 +1,267 @@
+pub mod metric;
+pub mod render;
+
+use self::metric::GridTooLarge;
+use self::metric::Manhattan;
+use self::metric::Metric;
+use std::fmt;
+use std::str::FromStr;
+
 #[derive(PartialEq, Eq, Clone, Debug)]
 pub struct Pair {
-    x: u32,
-    y: u32,
+    x: i64,
+    y: i64,
 }
 
-#[aoc_generator(day6, part1)]
-pub fn pair_generator(input: &str) -> Vec<Pair> {
+impl Pair {
+    pub fn new(x: i64, y: i64) -> Self {
+        Pair { x, y }
+    }
+
+    pub fn x(&self) -> i64 {
+        self.x
+    }
+
+    pub fn y(&self) -> i64 {
+        self.y
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ParsePairError {
+    // The line (counting from 1) doesn't hold two coordinates separated by a
+    // comma, whitespace or both
+    Malformed { line: usize },
+    // A coordinate on the line (counting from 1) isn't a signed 64-bit integer
+    InvalidCoordinate { line: usize, text: String },
+}
+
+impl fmt::Display for ParsePairError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParsePairError::Malformed { line } => {
+                write!(f, "line {}: expected two coordinates, like 1, 6", line)
+            }
+            ParsePairError::InvalidCoordinate { line, text } => {
+                write!(f, "line {}: invalid coordinate {:?}", line, text)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParsePairError {}
+
+fn parse_line(s: &str, line: usize) -> Result<Pair, ParsePairError> {
+    if s.matches(',').count() > 1 {
+        return Err(ParsePairError::Malformed { line });
+    }
+    let fields: Vec<&str> = s
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|f| !f.is_empty())
+        .collect();
+    if fields.len() != 2 {
+        return Err(ParsePairError::Malformed { line });
+    }
+    let coordinate = |text: &str| {
+        text.parse::<i64>()
+            .map_err(|_| ParsePairError::InvalidCoordinate {
+                line,
+                text: String::from(text),
+            })
+    };
+    Ok(Pair {
+        x: coordinate(fields[0])?,
+        y: coordinate(fields[1])?,
+    })
+}
+
+// Parses a single pair, e.g. "1, 6", "-3 4" or "5,5"
+impl FromStr for Pair {
+    type Err = ParsePairError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_line(s, 1)
+    }
+}
+
+// Parses one pair per line, skipping blank lines
+pub fn parse_pairs(input: &str) -> Result<Vec<Pair>, ParsePairError> {
     input
         .lines()
-        .map(|l| {
-            let parts = l.split(", ").collect::<Vec<_>>();
-            Pair {
-                x: parts[0].parse::<u32>().unwrap(),
-                y: parts[1].parse::<u32>().unwrap(),
-            }
-        })
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(i, l)| parse_line(l, i + 1))
         .collect()
 }
 
+// Regions in part 2 hold the locations whose total distance to every pair is
+// below this
+pub const SAFE_DISTANCE: u64 = 10000;
+
+#[aoc_generator(day6)]
+pub fn pair_generator(input: &str) -> Vec<Pair> {
+    match parse_pairs(input) {
+        Ok(pairs) => pairs,
+        Err(e) => panic!("{}", e),
+    }
+}
+
+// BoundingBox is the smallest rectangle, edges included, holding a set of
+// locations
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct BoundingBox {
+    pub min_x: i64,
+    pub max_x: i64,
+    pub min_y: i64,
+    pub max_y: i64,
+}
+
+impl BoundingBox {
+    // Gets the bounding box of the pairs, if there are any
+    pub fn new(pairs: &[Pair]) -> Option<Self> {
+        Some(BoundingBox {
+            min_x: pairs.iter().map(|p| p.x).min()?,
+            max_x: pairs.iter().map(|p| p.x).max()?,
+            min_y: pairs.iter().map(|p| p.y).min()?,
+            max_y: pairs.iter().map(|p| p.y).max()?,
+        })
+    }
+
+    // Gets the number of columns of locations in the box, saturating at
+    // u64::MAX
+    pub fn width(&self) -> u64 {
+        self.min_x.abs_diff(self.max_x).saturating_add(1)
+    }
+
+    // Gets the number of rows of locations in the box, saturating at u64::MAX
+    pub fn height(&self) -> u64 {
+        self.min_y.abs_diff(self.max_y).saturating_add(1)
+    }
+
+    pub fn contains(&self, x: i64, y: i64) -> bool {
+        self.min_x <= x && x <= self.max_x && self.min_y <= y && y <= self.max_y
+    }
+
+    // Whether the location is on the edge of the box
+    pub fn on_border(&self, x: i64, y: i64) -> bool {
+        self.contains(x, y)
+            && (x == self.min_x || x == self.max_x || y == self.min_y || y == self.max_y)
+    }
+
+    // Gets the box grown by the margin on every side, stopping at the edges
+    // of the grid
+    pub fn expand(&self, margin: i64) -> Self {
+        BoundingBox {
+            min_x: self.min_x.saturating_sub(margin),
+            max_x: self.max_x.saturating_add(margin),
+            min_y: self.min_y.saturating_sub(margin),
+            max_y: self.max_y.saturating_add(margin),
+        }
+    }
+
+    // Gets the smallest box holding both boxes
+    pub fn union(&self, other: &BoundingBox) -> Self {
+        BoundingBox {
+            min_x: self.min_x.min(other.min_x),
+            max_x: self.max_x.max(other.max_x),
+            min_y: self.min_y.min(other.min_y),
+            max_y: self.max_y.max(other.max_y),
+        }
+    }
+}
+
+// Gets the index of the pair strictly closest to the location, or None if
+// several pairs are equally close
+fn closest<M: Metric + ?Sized>(metric: &M, pairs: &[Pair], x: i64, y: i64) -> Option<usize> {
+    let mut best: Option<(usize, u64)> = None;
+    let mut tied = false;
+    for (i, p) in pairs.iter().enumerate() {
+        let d = metric.distance(p, x, y);
+        match best {
+            Some((_, b)) if d > b => {}
+            Some((_, b)) if d == b => tied = true,
+            _ => {
+                best = Some((i, d));
+                tied = false;
+            }
+        }
+    }
+    if tied {
+        None
+    } else {
+        best.map(|(i, _)| i)
+    }
+}
+
+// Gets the total distance from the location to every pair, saturating at
+// u64::MAX
+fn total_distance<M: Metric + ?Sized>(metric: &M, pairs: &[Pair], x: i64, y: i64) -> u64 {
+    pairs.iter().fold(0u64, |total, p| {
+        total.saturating_add(metric.distance(p, x, y))
+    })
+}
+
+// Finds the size of the largest area made up of the locations closest to a
+// single pair, ignoring infinite areas. Fails if the pairs are spread too far
+// apart to count the areas, see Metric::areas.
+pub fn largest_finite_area<M: Metric>(metric: &M, pairs: &[Pair]) -> Result<u64, GridTooLarge> {
+    Ok(metric
+        .areas(pairs)?
+        .into_iter()
+        .flatten()
+        .max()
+        .unwrap_or(0))
+}
+
+// Solved is a part's answer, or why it couldn't be found. Solutions must
+// return something printable, which a Result isn't, so this prints either.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Solved<T>(pub Result<T, GridTooLarge>);
+
+impl<T: fmt::Display> fmt::Display for Solved<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.0 {
+            Ok(answer) => write!(f, "{}", answer),
+            Err(e) => write!(f, "error: {}", e),
+        }
+    }
+}
+
 #[aoc(day6, part1)]
-pub fn day6_part1(input: &[Pair]) -> u32 {
-    0
+pub fn day6_part1(input: &[Pair]) -> Solved<u64> {
+    Solved(largest_finite_area(&Manhattan, input))
+}
+
+// Finds the number of locations whose total distance to every pair is below
+// the threshold. Fails if the region could be too large to count, see
+// Metric::safe_region_size.
+pub fn safe_region_size<M: Metric>(
+    metric: &M,
+    pairs: &[Pair],
+    threshold: u64,
+) -> Result<usize, GridTooLarge> {
+    metric.safe_region_size(pairs, threshold)
+}
+
+#[aoc(day6, part2)]
+pub fn day6_part2(input: &[Pair]) -> Solved<usize> {
+    Solved(safe_region_size(&Manhattan, input, SAFE_DISTANCE))
 }
 
 #[cfg(test)]
 mod tests {
+    use super::metric::Chebyshev;
+    use super::metric::SquaredEuclidean;
+    use super::metric::MAX_SCAN;
     use super::*;
 
+    pub const EXAMPLE: &str = "1, 1
+1, 6
+8, 3
+3, 4
+5, 5
+8, 9";
+
     #[test]
     fn test_pair_generator_1() {
         assert_eq!(pair_generator("200, 122")[0], Pair { x: 200, y: 122 });
@@ -38,5 +273,215 @@ mod tests {
     }
 
     #[test]
-    fn test_day6_part1() {}
+    fn test_parse_pairs() {
+        assert_eq!(
+            parse_pairs("-3, 4\n5 -6\n\t7,\t8  \n\n 9,10\n"),
+            Ok(vec![
+                Pair::new(-3, 4),
+                Pair::new(5, -6),
+                Pair::new(7, 8),
+                Pair::new(9, 10)
+            ])
+        );
+        assert_eq!(
+            "-9223372036854775808, 9223372036854775807".parse(),
+            Ok(Pair::new(i64::MIN, i64::MAX))
+        );
+        assert_eq!(parse_pairs(""), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_parse_pairs_errors() {
+        assert_eq!(
+            parse_pairs("1, 1\n\n1, 2, 3"),
+            Err(ParsePairError::Malformed { line: 3 })
+        );
+        assert_eq!(
+            parse_pairs("1, 1\n1,,2"),
+            Err(ParsePairError::Malformed { line: 2 })
+        );
+        assert_eq!(
+            "7".parse::<Pair>(),
+            Err(ParsePairError::Malformed { line: 1 })
+        );
+        let err = parse_pairs("1, 1\n2, x").unwrap_err();
+        assert_eq!(
+            err,
+            ParsePairError::InvalidCoordinate {
+                line: 2,
+                text: String::from("x")
+            }
+        );
+        assert_eq!(err.to_string(), "line 2: invalid coordinate \"x\"");
+        assert!(parse_pairs("9223372036854775808, 0").is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "line 1")]
+    fn test_pair_generator_invalid() {
+        pair_generator("a, b");
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let bounds = BoundingBox::new(&pair_generator(EXAMPLE)).unwrap();
+        assert_eq!(
+            bounds,
+            BoundingBox {
+                min_x: 1,
+                max_x: 8,
+                min_y: 1,
+                max_y: 9
+            }
+        );
+        assert_eq!((bounds.width(), bounds.height()), (8, 9));
+        assert!(bounds.contains(4, 4));
+        assert!(!bounds.contains(0, 4));
+        assert!(bounds.on_border(8, 5));
+        assert!(!bounds.on_border(4, 4));
+        assert_eq!(bounds.expand(1).width(), 10);
+        let other = BoundingBox::new(&[Pair::new(-5, 3)]).unwrap();
+        assert_eq!(
+            bounds.union(&other),
+            BoundingBox {
+                min_x: -5,
+                max_x: 8,
+                min_y: 1,
+                max_y: 9
+            }
+        );
+        assert_eq!(BoundingBox::new(&[]), None);
+
+        // The whole grid has more locations than a u64 counts
+        let grid =
+            BoundingBox::new(&[Pair::new(i64::MIN, i64::MIN), Pair::new(i64::MAX, 0)]).unwrap();
+        assert_eq!((grid.width(), grid.height()), (u64::MAX, (1 << 63) + 1));
+        assert_eq!(grid.expand(1), BoundingBox { max_y: 1, ..grid });
+    }
+
+    #[test]
+    fn test_negative_coordinates() {
+        // The example, shifted left and up past the origin
+        let shifted: Vec<Pair> = pair_generator(EXAMPLE)
+            .iter()
+            .map(|p| Pair::new(p.x - 1000, p.y - 20))
+            .collect();
+        assert_eq!(day6_part1(&shifted), Solved(Ok(17)));
+        assert_eq!(safe_region_size(&Manhattan, &shifted, 32), Ok(16));
+    }
+
+    #[test]
+    fn test_extreme_coordinates() {
+        let pairs = pair_generator("-9223372036854775808, 0\n9223372036854775807, 0");
+        assert_eq!(day6_part1(&pairs), Solved(Ok(0)));
+        assert_eq!(day6_part2(&pairs), Solved(Ok(0)));
+
+        // Both areas are infinite, so nothing needs counting
+        let corner = pair_generator("9223372036854775807, 9223372036854775807");
+        assert_eq!(day6_part1(&corner), Solved(Ok(0)));
+        // The grid ends at the corner, leaving a quarter of the diamond
+        assert_eq!(day6_part2(&corner), Solved(Ok(10000 * 10001 / 2)));
+
+        // The middle area is finite, but far too large to count
+        let diamond = pair_generator(
+            "-9223372036854775808, 0
+9223372036854775807, 0
+0, -9223372036854775808
+0, 9223372036854775807
+0, 0",
+        );
+        assert_eq!(
+            largest_finite_area(&Manhattan, &diamond),
+            Err(GridTooLarge {
+                width: u64::MAX,
+                height: u64::MAX,
+                limit: MAX_SCAN,
+            })
+        );
+        assert!(day6_part1(&diamond)
+            .to_string()
+            .starts_with("error: solving needs a"));
+        assert_eq!(day6_part2(&diamond), Solved(Ok(0)));
+    }
+
+    #[test]
+    fn test_wide_spread() {
+        // The window is too large to search breadth-first, so is scanned
+        // instead. The middle area is every location within 2499 steps of it.
+        let pairs = pair_generator("0, 0\n5000, 5000\n2500, 2500\n0, 5000\n5000, 0");
+        assert_eq!(
+            day6_part1(&pairs),
+            Solved(Ok(2 * 2500 * 2500 - 2 * 2500 + 1))
+        );
+    }
+
+    #[test]
+    fn test_closest() {
+        let pairs = pair_generator(EXAMPLE);
+        assert_eq!(closest(&Manhattan, &pairs, 0, 0), Some(0));
+        assert_eq!(closest(&Manhattan, &pairs, 5, 2), Some(4));
+        assert_eq!(closest(&Manhattan, &pairs, 0, 4), None);
+    }
+
+    #[test]
+    fn test_day6_part1() {
+        assert_eq!(day6_part1(&pair_generator(EXAMPLE)), Solved(Ok(17)));
+        assert_eq!(day6_part1(&[]), Solved(Ok(0)));
+    }
+
+    #[test]
+    fn test_safe_region_size() {
+        assert_eq!(
+            safe_region_size(&Manhattan, &pair_generator(EXAMPLE), 32),
+            Ok(16)
+        );
+        // Around a single pair the region is a diamond of 2t^2 - 2t + 1
+        assert_eq!(
+            safe_region_size(&Manhattan, &pair_generator("0, 0"), 2),
+            Ok(5)
+        );
+        assert_eq!(
+            safe_region_size(&Manhattan, &pair_generator("3, 3"), 100),
+            Ok(19801)
+        );
+        assert_eq!(safe_region_size(&Manhattan, &[], 32), Ok(0));
+        // Only the columns and rows near the pairs are summed, however far
+        // apart they are
+        assert_eq!(
+            safe_region_size(
+                &Manhattan,
+                &pair_generator("0, 0\n3000000000, 3000000000\n1, 1"),
+                10000
+            ),
+            Ok(0)
+        );
+        assert_eq!(
+            safe_region_size(&Manhattan, &pair_generator("0, 0\n1000000, 0"), 1_000_010),
+            Ok(9_000_041)
+        );
+    }
+
+    #[test]
+    fn test_metrics() {
+        let pairs = pair_generator(EXAMPLE);
+        assert_eq!(largest_finite_area(&Manhattan, &pairs), Ok(17));
+        assert_eq!(SquaredEuclidean.distance(&pairs[0], 8, 3), 53);
+        assert_eq!(SquaredEuclidean.distance(&pairs[3], 5, 5), 5);
+        assert_eq!(SquaredEuclidean.distance(&pairs[5], 1, 1), 113);
+        // Only the pairs at 3, 4 and 5, 5 are inside the hull
+        assert_eq!(
+            SquaredEuclidean.areas(&pairs),
+            Ok(vec![None, None, None, Some(10), Some(16), None])
+        );
+        assert_eq!(largest_finite_area(&SquaredEuclidean, &pairs), Ok(16));
+        assert_eq!(safe_region_size(&SquaredEuclidean, &pairs, 150), Ok(32));
+        assert_eq!(
+            safe_region_size(&SquaredEuclidean, &pair_generator("0, 0"), 5),
+            Ok(13)
+        );
+        assert_eq!(
+            safe_region_size(&Chebyshev, &pair_generator("0, 0"), 2),
+            Ok(9)
+        );
+    }
 }
@@ -0,0 +1,854 @@
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+
+use super::closest;
+use super::total_distance;
+use super::BoundingBox;
+use super::Pair;
+
+// The most locations, columns or rows the solvers keep in memory at once:
+// about 16 million, e.g. a 4096x4096 window
+pub const MAX_CELLS: u64 = 1 << 24;
+
+// The most locations the solvers scan one at a time, keeping nothing per
+// location: about a billion, e.g. a 32768x32768 window
+pub const MAX_SCAN: u64 = 1 << 30;
+
+// GridTooLarge is returned when solving would need a window of locations, or
+// a run of columns or rows, larger than the limit, MAX_CELLS or MAX_SCAN
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct GridTooLarge {
+    pub width: u64,
+    pub height: u64,
+    pub limit: u64,
+}
+
+impl fmt::Display for GridTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "solving needs a {}x{} grid, more than the {} locations allowed",
+            self.width, self.height, self.limit
+        )
+    }
+}
+
+impl std::error::Error for GridTooLarge {}
+
+// Checks the window holds no more locations than the limit
+fn check_cells(window: &BoundingBox, limit: u64) -> Result<(), GridTooLarge> {
+    let (width, height) = (window.width(), window.height());
+    if width.saturating_mul(height) > limit {
+        Err(GridTooLarge {
+            width,
+            height,
+            limit,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+// Pairs each area with whether it is infinite
+fn finite(areas: Vec<u64>, infinite: &[bool]) -> Vec<Option<u64>> {
+    areas
+        .into_iter()
+        .zip(infinite)
+        .map(|(area, &infinite)| if infinite { None } else { Some(area) })
+        .collect()
+}
+
+// Metric measures the distance from a pair to a location on the grid, and
+// knows which pairs are closest to infinitely many locations under it
+pub trait Metric {
+    fn distance(&self, p: &Pair, x: i64, y: i64) -> u64;
+
+    // Gets how far beyond the bounding box of `count` pairs a location can lie
+    // while its total distance to them stays below `total`
+    fn reach(&self, total: u64, count: usize) -> i64;
+
+    // Gets whether the area closest to each pair goes on forever
+    fn infinite(&self, pairs: &[Pair]) -> Vec<bool>;
+
+    // Gets the smallest and largest x and y of a window of locations holding
+    // every finite area, if there are any pairs
+    fn window(&self, pairs: &[Pair]) -> Option<BoundingBox>;
+
+    // Gets the size of the area closest to each pair, or None where the area
+    // is infinite. Counts the closest pair of every location in the window,
+    // so fails if some area is finite and the window holds more than
+    // MAX_SCAN locations.
+    fn areas(&self, pairs: &[Pair]) -> Result<Vec<Option<u64>>, GridTooLarge> {
+        scan_areas(self, pairs)
+    }
+
+    // Gets the number of locations whose total distance to every pair is
+    // below the threshold. Sums the distances of every location within reach
+    // of the bounding box, so fails if there are more than MAX_SCAN of them.
+    fn safe_region_size(&self, pairs: &[Pair], threshold: u64) -> Result<usize, GridTooLarge> {
+        scan_safe_region(self, pairs, threshold)
+    }
+}
+
+fn scan_areas<M: Metric + ?Sized>(
+    metric: &M,
+    pairs: &[Pair],
+) -> Result<Vec<Option<u64>>, GridTooLarge> {
+    let infinite = metric.infinite(pairs);
+    let mut areas = vec![0; pairs.len()];
+    if let (Some(window), true) = (metric.window(pairs), infinite.contains(&false)) {
+        check_cells(&window, MAX_SCAN)?;
+        for x in window.min_x..=window.max_x {
+            for y in window.min_y..=window.max_y {
+                if let Some(i) = closest(metric, pairs, x, y) {
+                    areas[i] += 1;
+                }
+            }
+        }
+    }
+    Ok(finite(areas, &infinite))
+}
+
+fn scan_safe_region<M: Metric + ?Sized>(
+    metric: &M,
+    pairs: &[Pair],
+    threshold: u64,
+) -> Result<usize, GridTooLarge> {
+    let window = match BoundingBox::new(pairs) {
+        Some(b) => b.expand(metric.reach(threshold, pairs.len()).saturating_add(1)),
+        None => return Ok(0),
+    };
+    check_cells(&window, MAX_SCAN)?;
+    let mut size = 0;
+    for x in window.min_x..=window.max_x {
+        for y in window.min_y..=window.max_y {
+            if total_distance(metric, pairs, x, y) < threshold {
+                size += 1;
+            }
+        }
+    }
+    Ok(size)
+}
+
+// Manhattan distance, |dx| + |dy|, as in the puzzle
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Manhattan;
+
+// Far enough east of a point p, under Manhattan distance a point s is at least
+// as close as p exactly when s lies in the closed quarter-plane cone east of p,
+// where s.x - p.x >= |s.y - p.y|. The four cones cover the plane, so p's area
+// is infinite exactly when one of its cones holds no other point. Points are
+// i128 so the differences of any two i64 coordinates, or their sums, fit.
+fn open_cones(points: &[(i128, i128)]) -> Vec<bool> {
+    let cones: [fn(i128, i128) -> bool; 4] = [
+        |dx, dy| dx >= dy.abs(),
+        |dx, dy| -dx >= dy.abs(),
+        |dx, dy| dy >= dx.abs(),
+        |dx, dy| -dy >= dx.abs(),
+    ];
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            cones.iter().any(|cone| {
+                !points
+                    .iter()
+                    .enumerate()
+                    .any(|(j, s)| j != i && cone(s.0 - p.0, s.1 - p.1))
+            })
+        })
+        .collect()
+}
+
+impl Metric for Manhattan {
+    // Saturates at u64::MAX, which only locations more than that far apart
+    // reach
+    fn distance(&self, p: &Pair, x: i64, y: i64) -> u64 {
+        p.x.abs_diff(x).saturating_add(p.y.abs_diff(y))
+    }
+
+    // Every step away from the box adds one to the distance to every pair
+    fn reach(&self, total: u64, count: usize) -> i64 {
+        i64::try_from(total / count as u64).unwrap_or(i64::MAX)
+    }
+
+    fn infinite(&self, pairs: &[Pair]) -> Vec<bool> {
+        let points: Vec<(i128, i128)> = pairs
+            .iter()
+            .map(|p| (i128::from(p.x), i128::from(p.y)))
+            .collect();
+        open_cones(&points)
+    }
+
+    fn window(&self, pairs: &[Pair]) -> Option<BoundingBox> {
+        BoundingBox::new(pairs)
+    }
+
+    // Grows every area at once, a step at a time, with a breadth-first search
+    // over the window from all the pairs. A location tied between pairs passes
+    // the tie on to the locations it reaches first. Keeps a cell per location
+    // in the window, so a window of more than MAX_CELLS locations is scanned
+    // one location at a time instead.
+    fn areas(&self, pairs: &[Pair]) -> Result<Vec<Option<u64>>, GridTooLarge> {
+        let infinite = self.infinite(pairs);
+        let mut areas = vec![0; pairs.len()];
+        if let (Some(window), true) = (self.window(pairs), infinite.contains(&false)) {
+            if check_cells(&window, MAX_CELLS).is_err() {
+                return scan_areas(self, pairs);
+            }
+            let BoundingBox { min_x, min_y, .. } = window;
+            let (width, height) = (window.width() as usize, window.height() as usize);
+            let mut owners = vec![Owner::Unreached; width * height];
+            let mut steps = vec![u32::MAX; width * height];
+
+            let mut frontier = Vec::new();
+            for (i, p) in pairs.iter().enumerate() {
+                let cell = min_y.abs_diff(p.y) as usize * width + min_x.abs_diff(p.x) as usize;
+                if owners[cell] == Owner::Unreached {
+                    owners[cell] = Owner::Pair(i);
+                    steps[cell] = 0;
+                    frontier.push(cell);
+                } else {
+                    owners[cell] = Owner::Tie;
+                }
+            }
+
+            let mut step = 0;
+            while !frontier.is_empty() {
+                step += 1;
+                let mut next = Vec::new();
+                for &cell in &frontier {
+                    let (x, y) = (cell % width, cell / width);
+                    let neighbours = [
+                        (x > 0, cell.wrapping_sub(1)),
+                        (x + 1 < width, cell + 1),
+                        (y > 0, cell.wrapping_sub(width)),
+                        (y + 1 < height, cell + width),
+                    ];
+                    for &(inside, neighbour) in &neighbours {
+                        if !inside {
+                            continue;
+                        }
+                        if steps[neighbour] == u32::MAX {
+                            steps[neighbour] = step;
+                            owners[neighbour] = owners[cell];
+                            next.push(neighbour);
+                        } else if steps[neighbour] == step && owners[neighbour] != owners[cell] {
+                            owners[neighbour] = Owner::Tie;
+                        }
+                    }
+                }
+                frontier = next;
+            }
+
+            for owner in owners {
+                if let Owner::Pair(i) = owner {
+                    areas[i] += 1;
+                }
+            }
+        }
+        Ok(finite(areas, &infinite))
+    }
+
+    // The total distance to every pair splits into a sum over x and a sum over
+    // y, so instead of summing per location, count for each column how many
+    // rows have a small enough sum over y. Only the columns and rows whose own
+    // sum is below the threshold can hold safe locations, so only their sums
+    // are kept, which fails if there are more than MAX_CELLS of either.
+    fn safe_region_size(&self, pairs: &[Pair], threshold: u64) -> Result<usize, GridTooLarge> {
+        let mut xs: Vec<i64> = pairs.iter().map(|p| p.x).collect();
+        let mut ys: Vec<i64> = pairs.iter().map(|p| p.y).collect();
+        xs.sort_unstable();
+        ys.sort_unstable();
+        let ((left, right), (top, bottom)) = match (below(&xs, threshold), below(&ys, threshold)) {
+            (Some(columns), Some(rows)) => (columns, rows),
+            _ => return Ok(0),
+        };
+        let width = left.abs_diff(right).saturating_add(1);
+        let height = top.abs_diff(bottom).saturating_add(1);
+        if width.max(height) > MAX_CELLS {
+            return Err(GridTooLarge {
+                width,
+                height,
+                limit: MAX_CELLS,
+            });
+        }
+        let columns = axis_sums(&xs, left, right);
+        let mut rows = axis_sums(&ys, top, bottom);
+        rows.sort_unstable();
+        Ok(columns
+            .iter()
+            .map(|&column| rows.partition_point(|&row| row < threshold - column))
+            .sum())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Owner {
+    Unreached,
+    Pair(usize),
+    Tie,
+}
+
+// Gets the sum of |c - coordinate| over every coordinate, saturating at
+// u64::MAX
+fn axis_sum(coordinates: &[i64], c: i64) -> u64 {
+    coordinates.iter().fold(0u64, |sum, &coordinate| {
+        sum.saturating_add(coordinate.abs_diff(c))
+    })
+}
+
+// Finds the largest d from 0 to max where holds(d), given holds(0) and that
+// holds stays false once it is false
+fn last_holding<F: Fn(i64) -> bool>(holds: F, max: i64) -> i64 {
+    let (mut low, mut high) = (0, max);
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        if holds(mid) {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    low
+}
+
+// Gets the first and last c where the axis sum of the sorted coordinates is
+// below the threshold, if there are any. The sum is convex with its minimum at
+// the median, so these form an interval around it. It is at least the spread
+// of the coordinates, and past them it grows by at least one per step, so the
+// interval reaches no further than twice the threshold either side.
+fn below(sorted: &[i64], threshold: u64) -> Option<(i64, i64)> {
+    let median = *sorted.get(sorted.len() / 2)?;
+    if axis_sum(sorted, median) >= threshold {
+        return None;
+    }
+    let reach = i64::try_from(threshold.saturating_mul(2)).unwrap_or(i64::MAX);
+    let holds = |c: Option<i64>| c.is_some_and(|c| axis_sum(sorted, c) < threshold);
+    let left = last_holding(|d| holds(median.checked_sub(d)), reach);
+    let right = last_holding(|d| holds(median.checked_add(d)), reach);
+    Some((median - left, median + right))
+}
+
+// Gets the axis sum of the sorted coordinates for every c from `from` to
+// `to`, which must all be below u64::MAX. Each step adds one per coordinate
+// behind and takes one away per coordinate ahead.
+fn axis_sums(sorted: &[i64], from: i64, to: i64) -> Vec<u64> {
+    let mut sum = axis_sum(sorted, from);
+    // coordinates at or behind the current position
+    let mut behind = sorted.partition_point(|&c| c <= from);
+    let mut sums = Vec::with_capacity(from.abs_diff(to) as usize + 1);
+    let mut position = from;
+    loop {
+        sums.push(sum);
+        if position >= to {
+            return sums;
+        }
+        // Every coordinate ahead adds at least one to the sum, so this can't
+        // wrap
+        let ahead = sorted.len() - behind;
+        sum = sum - ahead as u64 + behind as u64;
+        position += 1;
+        while behind < sorted.len() && sorted[behind] <= position {
+            behind += 1;
+        }
+    }
+}
+
+// Chebyshev distance, max(|dx|, |dy|), the number of moves a chess king needs
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Chebyshev;
+
+// Gets the location in rotated coordinates u = x + y and v = x - y, where
+// Chebyshev distance is (|du| + |dv|) / 2
+fn rotate(p: &Pair) -> (i128, i128) {
+    let (x, y) = (i128::from(p.x), i128::from(p.y));
+    (x + y, x - y)
+}
+
+// Gets the smallest and largest u and v of the pairs
+fn rotated_box(pairs: &[Pair]) -> Option<(i128, i128, i128, i128)> {
+    let u = |p: &Pair| rotate(p).0;
+    let v = |p: &Pair| rotate(p).1;
+    Some((
+        pairs.iter().map(u).min()?,
+        pairs.iter().map(u).max()?,
+        pairs.iter().map(v).min()?,
+        pairs.iter().map(v).max()?,
+    ))
+}
+
+impl Metric for Chebyshev {
+    fn distance(&self, p: &Pair, x: i64, y: i64) -> u64 {
+        p.x.abs_diff(x).max(p.y.abs_diff(y))
+    }
+
+    // Every step away from the box adds at least one to the distance to every
+    // pair
+    fn reach(&self, total: u64, count: usize) -> i64 {
+        i64::try_from(total / count as u64).unwrap_or(i64::MAX)
+    }
+
+    // Chebyshev distance is half the Manhattan distance in rotated
+    // coordinates, so the Manhattan cones decide which areas are infinite.
+    // Every other rotated location is a grid location, but the rays a pair
+    // owns along the rotated axes step by 2 and stay on the grid.
+    fn infinite(&self, pairs: &[Pair]) -> Vec<bool> {
+        let points: Vec<(i128, i128)> = pairs.iter().map(rotate).collect();
+        open_cones(&points)
+    }
+
+    // The locations within one of the rotated box, as far as the grid goes
+    fn window(&self, pairs: &[Pair]) -> Option<BoundingBox> {
+        let (min_u, max_u, min_v, max_v) = rotated_box(pairs)?;
+        let clamp = |c: i128| c.clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64;
+        Some(BoundingBox {
+            min_x: clamp((min_u + min_v).div_euclid(2) - 1),
+            max_x: clamp((max_u + max_v).div_euclid(2) + 1),
+            min_y: clamp((min_u - max_v).div_euclid(2) - 1),
+            max_y: clamp((max_u - min_v).div_euclid(2) + 1),
+        })
+    }
+}
+
+// Squared Euclidean distance, dx^2 + dy^2. It orders locations the same way as
+// Euclidean distance, but stays exact in integers, as long as coordinates are
+// within about a billion of each other.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct SquaredEuclidean;
+
+fn isqrt(n: u64) -> u64 {
+    let square = |root: u64| root.checked_mul(root);
+    let mut root = (n as f64).sqrt() as u64;
+    while square(root).is_none_or(|s| s > n) {
+        root -= 1;
+    }
+    while square(root + 1).is_some_and(|s| s <= n) {
+        root += 1;
+    }
+    root
+}
+
+// Gets the sign of the cross product (a - o) x (b - o), i.e. which side of the
+// line from o through a the pair b lies on. Differences of coordinates fit in
+// i128 but their products may not, so the two products are compared by sign
+// and then magnitude.
+fn cross(o: &Pair, a: &Pair, b: &Pair) -> Ordering {
+    let diff = |p: i64, q: i64| i128::from(p) - i128::from(q);
+    let product = |m: i128, n: i128| (m.signum() * n.signum(), m.unsigned_abs() * n.unsigned_abs());
+    let (left_sign, left) = product(diff(a.x, o.x), diff(b.y, o.y));
+    let (right_sign, right) = product(diff(a.y, o.y), diff(b.x, o.x));
+    match left_sign.cmp(&right_sign) {
+        Ordering::Equal if left_sign < 0 => right.cmp(&left),
+        Ordering::Equal => left.cmp(&right),
+        sign => sign,
+    }
+}
+
+// Whether the pair lies on the boundary of the convex hull of the pairs, i.e.
+// some line through it and another pair has every pair on one side
+fn on_hull(pairs: &[Pair], p: &Pair) -> bool {
+    let mut others = pairs.iter().filter(|&q| q != p).peekable();
+    if others.peek().is_none() {
+        return true;
+    }
+    others.any(|q| {
+        pairs.iter().all(|s| cross(p, q, s) != Ordering::Less)
+            || pairs.iter().all(|s| cross(p, q, s) != Ordering::Greater)
+    })
+}
+
+// Gets the x range of the locations in row y strictly closer to pairs[i] than
+// to any other pair, if there are any. Each other pair bounds x from one side,
+// as the locations closer to pairs[i] form a half-plane. Works in i128
+// relative to pairs[i], which can't overflow while the pairs and y are within
+// 2^62 of it.
+fn row_interval(pairs: &[Pair], i: usize, y: i64) -> Option<(i64, i64)> {
+    let (px, py) = (i128::from(pairs[i].x), i128::from(pairs[i].y));
+    let dy = i128::from(y) - py;
+    // Relative to px, so the range starts as the whole row of the grid
+    let (mut low, mut high) = (i128::from(i64::MIN) - px, i128::from(i64::MAX) - px);
+    for (j, s) in pairs.iter().enumerate() {
+        if j == i {
+            continue;
+        }
+        let (sx, sy) = (i128::from(s.x) - px, i128::from(s.y) - py);
+        // Closer to pairs[i] when a * x < c
+        let a = 2 * sx;
+        let c = sx * sx + sy * sy - 2 * dy * sy;
+        if a > 0 {
+            high = high.min((c - 1).div_euclid(a));
+        } else if a < 0 {
+            low = low.max((-c).div_euclid(-a) + 1);
+        } else if c <= 0 {
+            return None;
+        }
+    }
+    if low <= high {
+        Some(((px + low) as i64, (px + high) as i64))
+    } else {
+        None
+    }
+}
+
+// Gets every (y, low x, high x) row of the area closest to pairs[i], which
+// must be finite. Areas are convex and hold their own pair, unless another
+// pair shares its location, so the rows run on from the pair's own row. Fails
+// if the pairs span more than MAX_CELLS columns or rows, or the area has more
+// than MAX_CELLS rows, which also keeps row_interval from overflowing.
+fn rows(pairs: &[Pair], i: usize) -> Result<Vec<(i64, i64, i64)>, GridTooLarge> {
+    let mut rows = Vec::new();
+    let bounds = match BoundingBox::new(pairs) {
+        Some(b) => b,
+        None => return Ok(rows),
+    };
+    let too_large = |height| GridTooLarge {
+        width: bounds.width(),
+        height,
+        limit: MAX_CELLS,
+    };
+    if bounds.width().max(bounds.height()) > MAX_CELLS {
+        return Err(too_large(bounds.height()));
+    }
+    let py = pairs[i].y;
+    for &(start, step) in &[(Some(py), 1), (py.checked_sub(1), -1)] {
+        let mut y = start;
+        while let Some(row) = y {
+            let (low, high) = match row_interval(pairs, i, row) {
+                Some(interval) => interval,
+                None => break,
+            };
+            if rows.len() as u64 == MAX_CELLS {
+                return Err(too_large(MAX_CELLS + 1));
+            }
+            rows.push((row, low, high));
+            y = row.checked_add(step);
+        }
+        if rows.is_empty() {
+            break;
+        }
+    }
+    Ok(rows)
+}
+
+impl Metric for SquaredEuclidean {
+    fn distance(&self, p: &Pair, x: i64, y: i64) -> u64 {
+        let (dx, dy) = (p.x.abs_diff(x), p.y.abs_diff(y));
+        dx.saturating_mul(dx).saturating_add(dy.saturating_mul(dy))
+    }
+
+    // A location d beyond the box is at least d^2 from every pair
+    fn reach(&self, total: u64, count: usize) -> i64 {
+        isqrt(total / count as u64) as i64
+    }
+
+    // The area of a pair on the convex hull is unbounded: moving away from the
+    // hull at right angles to a side through it gets strictly closer to it
+    // than to any other pair, and those steps can be whole grid steps. The area
+    // of a pair inside the hull is bounded. Pairs sharing a location tie
+    // everywhere, so have no area at all.
+    fn infinite(&self, pairs: &[Pair]) -> Vec<bool> {
+        pairs
+            .iter()
+            .map(|p| on_hull(pairs, p) && pairs.iter().filter(|&q| q == p).count() == 1)
+            .collect()
+    }
+
+    // The bounding box, grown to hold the rows of every finite area. An area
+    // too large to trace is too large to scan, so then the box is grown past
+    // what can be scanned instead.
+    fn window(&self, pairs: &[Pair]) -> Option<BoundingBox> {
+        let mut window = BoundingBox::new(pairs)?;
+        let infinite = self.infinite(pairs);
+        for i in (0..pairs.len()).filter(|&i| !infinite[i]) {
+            let rows = match rows(pairs, i) {
+                Ok(rows) => rows,
+                Err(_) => return Some(window.expand(MAX_CELLS as i64)),
+            };
+            for (y, low, high) in rows {
+                window = window.union(&BoundingBox {
+                    min_x: low,
+                    max_x: high,
+                    min_y: y,
+                    max_y: y,
+                });
+            }
+        }
+        Some(window)
+    }
+
+    // Adds up the rows of each finite area rather than scanning the window, so
+    // only fails if some area is finite and either the pairs span more than
+    // MAX_CELLS columns or rows or the area has more than MAX_CELLS rows.
+    // Sizes saturate at u64::MAX.
+    fn areas(&self, pairs: &[Pair]) -> Result<Vec<Option<u64>>, GridTooLarge> {
+        let infinite = self.infinite(pairs);
+        (0..pairs.len())
+            .map(|i| {
+                if infinite[i] {
+                    return Ok(None);
+                }
+                let size = rows(pairs, i)?.iter().fold(0u64, |size, &(_, low, high)| {
+                    size.saturating_add(low.abs_diff(high).saturating_add(1))
+                });
+                Ok(Some(size))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::pair_generator;
+    use super::super::tests::EXAMPLE;
+    use super::*;
+    use day5::generator::XorShift;
+
+    fn random_pairs(rng: &mut XorShift, count: usize, size: usize) -> Vec<Pair> {
+        (0..count)
+            .map(|_| Pair {
+                x: rng.below(size) as i64,
+                y: rng.below(size) as i64,
+            })
+            .collect()
+    }
+
+    // Counts the locations within `radius` of the bounding box closest to each
+    // pair. Finite areas stop growing once the radius covers them; infinite
+    // ones keep growing.
+    fn areas_within<M: Metric>(metric: &M, pairs: &[Pair], radius: i64) -> Vec<u64> {
+        let BoundingBox {
+            min_x,
+            max_x,
+            min_y,
+            max_y,
+        } = BoundingBox::new(pairs).unwrap();
+        let mut areas = vec![0; pairs.len()];
+        for x in min_x - radius..=max_x + radius {
+            for y in min_y - radius..=max_y + radius {
+                if let Some(i) = closest(metric, pairs, x, y) {
+                    areas[i] += 1;
+                }
+            }
+        }
+        areas
+    }
+
+    fn check_areas<M: Metric>(metric: &M, seed: u64) {
+        let mut rng = XorShift::new(seed);
+        for count in 1..8 {
+            let pairs = random_pairs(&mut rng, count, 12);
+            let areas = metric.areas(&pairs).unwrap();
+            let near = areas_within(metric, &pairs, 40);
+            let far = areas_within(metric, &pairs, 80);
+            for i in 0..pairs.len() {
+                match areas[i] {
+                    Some(area) => {
+                        assert_eq!(area, near[i], "{:?} pair {}", pairs, i);
+                        assert_eq!(area, far[i], "{:?} pair {}", pairs, i);
+                    }
+                    None => assert!(far[i] > near[i], "{:?} pair {}", pairs, i),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_distance() {
+        let p = Pair { x: 1, y: 1 };
+        assert_eq!(Manhattan.distance(&p, 4, -3), 7);
+        assert_eq!(Chebyshev.distance(&p, 4, -3), 4);
+        assert_eq!(SquaredEuclidean.distance(&p, 4, -3), 25);
+
+        let far = Pair {
+            x: i64::MIN,
+            y: i64::MIN,
+        };
+        assert_eq!(Manhattan.distance(&far, i64::MAX, i64::MAX), u64::MAX);
+        assert_eq!(Chebyshev.distance(&far, i64::MAX, 0), u64::MAX);
+        assert_eq!(SquaredEuclidean.distance(&far, 0, 0), u64::MAX);
+    }
+
+    #[test]
+    fn test_manhattan_areas() {
+        assert_eq!(
+            Manhattan.areas(&pair_generator(EXAMPLE)),
+            Ok(vec![None, None, None, Some(9), Some(17), None])
+        );
+        check_areas(&Manhattan, 0x2018_0006);
+    }
+
+    #[test]
+    fn test_manhattan_matches_scan() {
+        let mut rng = XorShift::new(0x2018_3006);
+        for count in 1..30 {
+            let pairs = random_pairs(&mut rng, count, 20);
+            assert_eq!(Manhattan.areas(&pairs), scan_areas(&Manhattan, &pairs));
+            for &threshold in &[1, 10, 50, 200] {
+                assert_eq!(
+                    Manhattan.safe_region_size(&pairs, threshold),
+                    scan_safe_region(&Manhattan, &pairs, threshold),
+                    "{:?} below {}",
+                    pairs,
+                    threshold
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_manhattan_infinite() {
+        // Every cone around (5, 5) holds another pair, if only on its edge
+        let pairs = pair_generator("5, 5\n9, 9\n1, 9\n5, 0\n0, 5");
+        assert_eq!(
+            Manhattan.infinite(&pairs),
+            vec![false, true, true, true, true]
+        );
+        // Without (5, 0), nothing is in the cone to the south
+        assert!(Manhattan.infinite(&pair_generator("5, 5\n9, 9\n1, 9\n0, 5"))[0]);
+        // Pairs sharing a location have no area
+        assert_eq!(
+            Manhattan.infinite(&pair_generator("5, 5\n5, 5")),
+            vec![false, false]
+        );
+    }
+
+    #[test]
+    fn test_axis_sums() {
+        assert_eq!(axis_sums(&[1, 3, 3], -1, 5), vec![10, 7, 4, 3, 2, 5, 8]);
+        assert_eq!(axis_sums(&[], 0, 2), vec![0, 0, 0]);
+    }
+
+    #[test]
+    fn test_below() {
+        assert_eq!(below(&[1, 3, 3], 6), Some((1, 4)));
+        assert_eq!(below(&[1, 3, 3], 2), None);
+        // The sum is flat between the two middle coordinates
+        assert_eq!(below(&[0, 10], 11), Some((0, 10)));
+        assert_eq!(below(&[0, 10], 13), Some((-1, 11)));
+        assert_eq!(below(&[], 10), None);
+    }
+
+    #[test]
+    fn test_grid_too_large() {
+        // The middle pair's area is finite, but the window is 100001 wide
+        let pairs = pair_generator("0, 0\n100000, 100000\n50000, 50000\n0, 100000\n100000, 0");
+        let too_large = Err(GridTooLarge {
+            width: 100_001,
+            height: 100_001,
+            limit: MAX_SCAN,
+        });
+        assert_eq!(Manhattan.areas(&pairs), too_large);
+        // Counting rows needs no grid
+        assert_eq!(
+            SquaredEuclidean.areas(&pairs),
+            Ok(vec![None, None, Some(4_999_900_001), None, None])
+        );
+        assert!(Chebyshev.areas(&pairs).is_err());
+        // With every area infinite there is nothing to count
+        assert_eq!(
+            Manhattan.areas(&pair_generator("0, 0\n100000, 100000")),
+            Ok(vec![None, None])
+        );
+        assert!(Manhattan
+            .safe_region_size(&pair_generator("0, 0"), 1 << 40)
+            .is_err());
+        assert!(Chebyshev
+            .safe_region_size(&pair_generator("0, 0"), 1 << 20)
+            .is_err());
+    }
+
+    #[test]
+    fn test_extreme_coordinates() {
+        let diamond = pair_generator(
+            "-9223372036854775808, 0
+9223372036854775807, 0
+0, -9223372036854775808
+0, 9223372036854775807
+0, 0",
+        );
+        let infinite = vec![true, true, true, true, false];
+        assert_eq!(Manhattan.infinite(&diamond), infinite);
+        assert_eq!(Chebyshev.infinite(&diamond), infinite);
+        assert_eq!(SquaredEuclidean.infinite(&diamond), infinite);
+        assert!(Manhattan.areas(&diamond).is_err());
+        assert!(Chebyshev.areas(&diamond).is_err());
+        assert!(SquaredEuclidean.areas(&diamond).is_err());
+
+        // The rotated box is twice the size of the grid
+        assert_eq!(
+            Chebyshev.window(&diamond),
+            Some(BoundingBox {
+                min_x: i64::MIN,
+                max_x: i64::MAX,
+                min_y: i64::MIN,
+                max_y: i64::MAX,
+            })
+        );
+        assert_eq!(
+            Chebyshev.safe_region_size(&diamond, 10000),
+            Err(GridTooLarge {
+                width: u64::MAX,
+                height: u64::MAX,
+                limit: MAX_SCAN,
+            })
+        );
+
+        // (0, 0) is on the diagonal between the far corners, which is inside the
+        // hull once (1, 0) and (0, 1) are either side of it
+        let corners = pair_generator(
+            "-9223372036854775808, -9223372036854775808
+9223372036854775807, 9223372036854775807
+1, 0
+0, 1
+0, 0",
+        );
+        assert_eq!(SquaredEuclidean.infinite(&corners), infinite);
+        assert_eq!(
+            SquaredEuclidean.infinite(&corners[..3]),
+            vec![true, true, true]
+        );
+        assert_eq!(isqrt(u64::MAX), 4_294_967_295);
+    }
+
+    #[test]
+    fn test_chebyshev_areas() {
+        check_areas(&Chebyshev, 0x2018_1006);
+    }
+
+    #[test]
+    fn test_squared_euclidean_areas() {
+        check_areas(&SquaredEuclidean, 0x2018_2006);
+        let pairs = pair_generator(EXAMPLE);
+        assert_eq!(
+            SquaredEuclidean.areas(&pairs),
+            scan_areas(&SquaredEuclidean, &pairs)
+        );
+    }
+
+    #[test]
+    fn test_on_hull() {
+        // (1, 0) lies on a side of the hull, (1, 1) inside it
+        let pairs = pair_generator("0, 0\n1, 0\n2, 0\n1, 1\n1, 3");
+        assert_eq!(
+            SquaredEuclidean.infinite(&pairs),
+            vec![true, true, true, false, true]
+        );
+        assert_eq!(
+            SquaredEuclidean.infinite(&pair_generator("4, 4\n4, 4\n9, 9")),
+            vec![false, false, true]
+        );
+    }
+
+    #[test]
+    fn test_reach() {
+        assert_eq!(Manhattan.reach(32, 6), 5);
+        assert_eq!(Chebyshev.reach(32, 6), 5);
+        assert_eq!(SquaredEuclidean.reach(32, 2), 4);
+        assert_eq!(SquaredEuclidean.reach(35, 2), 4);
+        assert_eq!(Manhattan.reach(u64::MAX, 1), i64::MAX);
+        assert_eq!(Chebyshev.reach(u64::MAX, 1), i64::MAX);
+        assert_eq!(SquaredEuclidean.reach(u64::MAX, 1), 4_294_967_295);
+    }
+}
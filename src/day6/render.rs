@@ -0,0 +1,176 @@
+use super::closest;
+use super::metric::Manhattan;
+use super::total_distance;
+use super::BoundingBox;
+use super::Pair;
+
+// Renderers for the areas closest to each pair, drawn over the locations in a
+// bounding box, e.g. the pairs' own box expanded by a margin. Rows go from
+// min_y down to max_y and columns from min_x across to max_x, so negative
+// coordinates are drawn like any other.
+
+fn is_pair(pairs: &[Pair], x: i64, y: i64) -> bool {
+    pairs.iter().any(|p| p.x == x && p.y == y)
+}
+
+// Draws the areas as in the puzzle: each area in the lowercase letter of its
+// pair, the pairs themselves in uppercase, and '.' where pairs tie. Letters
+// wrap around after the 26th pair.
+pub fn render_ascii(pairs: &[Pair], bounds: &BoundingBox) -> String {
+    let mut out = String::new();
+    for y in bounds.min_y..=bounds.max_y {
+        for x in bounds.min_x..=bounds.max_x {
+            let c = match closest(&Manhattan, pairs, x, y) {
+                Some(i) => {
+                    let letter = (b'a' + (i % 26) as u8) as char;
+                    if is_pair(pairs, x, y) {
+                        letter.to_ascii_uppercase()
+                    } else {
+                        letter
+                    }
+                }
+                None => '.',
+            };
+            out.push(c);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+const TIE: [u8; 3] = [40, 40, 40];
+const PAIR: [u8; 3] = [255, 255, 255];
+
+// Picks well-separated colours by stepping the hue by the golden ratio
+fn colour(i: usize) -> [u8; 3] {
+    let hue = (i as f64 * 0.618_033_988_75).fract() * 6.0;
+    let (saturation, value) = (0.6, 0.9);
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = value - chroma;
+    let scale = |c: f64| ((c + m) * 255.0).round() as u8;
+    [scale(r), scale(g), scale(b)]
+}
+
+// Draws the areas as a binary PPM image, one colour per pair, dark grey where
+// pairs tie and white for the pairs themselves. Given a threshold, the part 2
+// safe region is overlaid by lightening every location in it.
+pub fn render_ppm(pairs: &[Pair], bounds: &BoundingBox, safe: Option<u64>) -> Vec<u8> {
+    let mut out = format!("P6\n{} {}\n255\n", bounds.width(), bounds.height()).into_bytes();
+    for y in bounds.min_y..=bounds.max_y {
+        for x in bounds.min_x..=bounds.max_x {
+            let mut pixel = if is_pair(pairs, x, y) {
+                PAIR
+            } else {
+                match closest(&Manhattan, pairs, x, y) {
+                    Some(i) => colour(i),
+                    None => TIE,
+                }
+            };
+            if let Some(threshold) = safe {
+                let total = total_distance(&Manhattan, pairs, x, y);
+                if total < threshold {
+                    for c in pixel.iter_mut() {
+                        *c = ((u16::from(*c) + 255) / 2) as u8;
+                    }
+                }
+            }
+            out.extend_from_slice(&pixel);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::pair_generator;
+    use super::super::tests::EXAMPLE;
+    use super::*;
+
+    // The 10x10 grid drawn in the puzzle, from 0, 0 to 9, 9
+    const GRID: BoundingBox = BoundingBox {
+        min_x: 0,
+        max_x: 9,
+        min_y: 0,
+        max_y: 9,
+    };
+
+    #[test]
+    fn test_render_ascii() {
+        assert_eq!(
+            render_ascii(&pair_generator(EXAMPLE), &GRID),
+            "aaaaa.cccc
+aAaaa.cccc
+aaaddecccc
+aadddeccCc
+..dDdeeccc
+bb.deEeecc
+bBb.eeee..
+bbb.eeefff
+bbb.eeffff
+bbb.ffffFf
+"
+        );
+    }
+
+    #[test]
+    fn test_render_negative_coordinates() {
+        let pairs = pair_generator("-3, -1\n1, 0");
+        let bounds = BoundingBox::new(&pairs).unwrap().expand(1);
+        assert_eq!(
+            render_ascii(&pairs, &bounds),
+            "aaaabbb
+aAaabbb
+aaabbBb
+aaabbbb
+"
+        );
+        let image = render_ppm(&pairs, &bounds, None);
+        assert!(image.starts_with(b"P6\n7 4\n255\n"));
+        assert_eq!(image.len(), "P6\n7 4\n255\n".len() + 3 * 28);
+    }
+
+    fn pixel(image: &[u8], width: u32, x: u32, y: u32) -> [u8; 3] {
+        let header = format!("P6\n{} 10\n255\n", width).len();
+        let i = header + 3 * (y * width + x) as usize;
+        [image[i], image[i + 1], image[i + 2]]
+    }
+
+    #[test]
+    fn test_render_ppm() {
+        let pairs = pair_generator(EXAMPLE);
+        let image = render_ppm(&pairs, &GRID, None);
+        assert!(image.starts_with(b"P6\n10 10\n255\n"));
+        assert_eq!(image.len(), "P6\n10 10\n255\n".len() + 300);
+        assert_eq!(pixel(&image, 10, 1, 1), PAIR);
+        assert_eq!(pixel(&image, 10, 5, 0), TIE);
+        assert_eq!(pixel(&image, 10, 0, 0), colour(0));
+        assert_eq!(pixel(&image, 10, 9, 9), colour(5));
+    }
+
+    #[test]
+    fn test_render_ppm_safe_region() {
+        let pairs = pair_generator(EXAMPLE);
+        let plain = render_ppm(&pairs, &GRID, None);
+        let overlaid = render_ppm(&pairs, &GRID, Some(32));
+        // (4, 3) is in the safe region, (0, 0) isn't
+        assert_ne!(pixel(&plain, 10, 4, 3), pixel(&overlaid, 10, 4, 3));
+        assert_eq!(pixel(&plain, 10, 0, 0), pixel(&overlaid, 10, 0, 0));
+    }
+
+    #[test]
+    fn test_colours_differ() {
+        for i in 0..50 {
+            assert_ne!(colour(i), colour(i + 1));
+            assert_ne!(colour(i), TIE);
+        }
+    }
+}
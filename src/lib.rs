@@ -2,6 +2,7 @@
 extern crate lazy_static;
 
 extern crate aoc_runner;
+#[cfg(feature = "chrono")]
 extern crate chrono;
 extern crate im;
 extern crate regex;
@@ -15,5 +16,6 @@ pub mod day3;
 pub mod day4;
 pub mod day5;
 pub mod day6;
+pub mod registry;
 
 aoc_lib! { year = 2018 }
@@ -1,7 +1,302 @@
 extern crate advent_of_code_2018;
-extern crate aoc_runner;
-extern crate aoc_runner_derive;
 
-use aoc_runner_derive::aoc_main;
+use std::env;
+use std::fs;
+use std::io;
+use std::io::Read;
+use std::path::Path;
+use std::process;
+use std::time::Instant;
 
-aoc_main! { lib = advent_of_code_2018 }
+use advent_of_code_2018::registry;
+use advent_of_code_2018::registry::Solution;
+
+const USAGE: &str = "Usage: advent-of-code-2018 [options]
+
+Runs the solutions on the puzzle inputs. With no options, runs both parts of
+the latest day.
+
+Options:
+    -d, --day DAY       run the given day
+    -p, --part PART     run only part 1 or 2
+    -n, --name NAME     run the named alternate, e.g. immutable or find_outlier2
+    -i, --input PATH    read the input from PATH, or stdin if PATH is -,
+                        instead of input/2018/dayN.txt in the crate's
+                        directory
+    -a, --all           run every day, or every alternate of the given day
+    -l, --list          list the solutions instead of running them
+    -v, --verify        run every alternate of each part, of the given day or
+                        else every day, and fail if their answers differ
+    -h, --help          print this message";
+
+#[derive(Debug, PartialEq, Eq, Default)]
+struct Options {
+    day: Option<u32>,
+    part: Option<u32>,
+    name: Option<String>,
+    input: Option<String>,
+    all: bool,
+    list: bool,
+    verify: bool,
+    help: bool,
+}
+
+fn parse_number(flag: &str, value: Option<String>) -> Result<u32, String> {
+    let value = value.ok_or(format!("{} needs a value", flag))?;
+    value
+        .parse()
+        .map_err(|_| format!("{} expects a number, got {:?}", flag, value))
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+    let mut options = Options::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-d" | "--day" => options.day = Some(parse_number(&arg, args.next())?),
+            "-p" | "--part" => {
+                let part = parse_number(&arg, args.next())?;
+                if part != 1 && part != 2 {
+                    return Err(format!("there is no part {}", part));
+                }
+                options.part = Some(part);
+            }
+            "-n" | "--name" => {
+                options.name = Some(args.next().ok_or(format!("{} needs a value", arg))?)
+            }
+            "-i" | "--input" => {
+                options.input = Some(args.next().ok_or(format!("{} needs a value", arg))?)
+            }
+            "-a" | "--all" => options.all = true,
+            "-l" | "--list" => options.list = true,
+            "-v" | "--verify" => options.verify = true,
+            "-h" | "--help" => options.help = true,
+            _ => return Err(format!("unknown option {:?}", arg)),
+        }
+    }
+    if options.input.is_some() && options.day.is_none() {
+        return Err(String::from("--input needs --day"));
+    }
+    if options.verify && options.name.is_some() {
+        return Err(String::from(
+            "--verify runs every alternate, so takes no --name",
+        ));
+    }
+    Ok(options)
+}
+
+// Picks the solutions the options ask for, in day, part and table order
+fn select(options: &Options) -> Vec<&'static Solution> {
+    let latest = registry::solutions().iter().map(|s| s.day).max();
+    let day = match options.day {
+        Some(day) => Some(day),
+        None if options.all => None,
+        None => latest,
+    };
+    registry::solutions()
+        .iter()
+        .filter(|s| day.is_none_or(|d| s.day == d))
+        .filter(|s| options.part.is_none_or(|p| s.part == p))
+        .filter(|s| match &options.name {
+            Some(name) => s.name == Some(name.as_str()),
+            None => options.all || options.list || s.is_default(),
+        })
+        .collect()
+}
+
+// Reads the puzzle input, without the trailing newline
+fn read_input(day: u32, path: Option<&str>) -> Result<String, String> {
+    let input = match path {
+        Some("-") => {
+            let mut input = String::new();
+            io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|e| format!("can't read stdin: {}", e))?;
+            input
+        }
+        Some(path) => {
+            fs::read_to_string(path).map_err(|e| format!("can't read {}: {}", path, e))?
+        }
+        None => {
+            // The checked-in inputs, wherever the runner is started from
+            let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("input/2018")
+                .join(format!("day{}.txt", day));
+            fs::read_to_string(&path)
+                .map_err(|e| format!("can't read {}: {}", path.display(), e))?
+        }
+    };
+    Ok(String::from(input.trim_end_matches(['\r', '\n'])))
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let solutions = select(options);
+    if solutions.is_empty() {
+        return Err(String::from("no solution matches"));
+    }
+    if options.list {
+        for solution in solutions {
+            println!("{:<30} {}", solution.label(), solution.description);
+        }
+        return Ok(());
+    }
+    let mut input: Option<(u32, String)> = None;
+    for solution in solutions {
+        if input.as_ref().is_none_or(|(day, _)| *day != solution.day) {
+            input = Some((
+                solution.day,
+                read_input(solution.day, options.input.as_deref())?,
+            ));
+        }
+        let (_, text) = input.as_ref().unwrap();
+        let start = Instant::now();
+        let answer = solution
+            .run(text)
+            .map_err(|e| format!("{}: {}", solution.label(), e))?;
+        println!("{}: {} ({:?})", solution.label(), answer, start.elapsed());
+    }
+    Ok(())
+}
+
+// Cross-checks the alternates of every selected part, printing each answer,
+// and returns whether they all agree
+fn verify(options: &Options) -> Result<bool, String> {
+    let mut parts: Vec<(u32, u32)> = registry::solutions()
+        .iter()
+        .filter(|s| options.day.is_none_or(|d| s.day == d))
+        .filter(|s| options.part.is_none_or(|p| s.part == p))
+        .map(|s| (s.day, s.part))
+        .collect();
+    parts.dedup();
+    if parts.is_empty() {
+        return Err(String::from("no solution matches"));
+    }
+    let mut agreed = true;
+    let mut input: Option<(u32, String)> = None;
+    for (day, part) in parts {
+        if input.as_ref().is_none_or(|(d, _)| *d != day) {
+            input = Some((day, read_input(day, options.input.as_deref())?));
+        }
+        let (_, text) = input.as_ref().unwrap();
+        let check = registry::cross_check(day, part, text);
+        let status = if check.agrees() {
+            "ok"
+        } else {
+            agreed = false;
+            "MISMATCH"
+        };
+        println!("Day {} - Part {}: {}", day, part, status);
+        for run in &check.runs {
+            let answer = match &run.answer {
+                Ok(answer) => answer.to_string(),
+                Err(e) => format!("error: {}", e),
+            };
+            println!(
+                "    {:<16} {} ({:?})",
+                run.solution.name.unwrap_or("(default)"),
+                answer,
+                run.elapsed
+            );
+        }
+    }
+    Ok(agreed)
+}
+
+fn main() {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+    if options.help {
+        println!("{}", USAGE);
+        return;
+    }
+    if options.verify {
+        match verify(&options) {
+            Ok(true) => {}
+            Ok(false) => {
+                eprintln!("alternates disagree");
+                process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
+    } else if let Err(e) = run(&options) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Result<Options, String> {
+        parse_args(s.split_whitespace().map(String::from))
+    }
+
+    fn labels(s: &str) -> Vec<String> {
+        select(&args(s).unwrap())
+            .into_iter()
+            .map(Solution::label)
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(args(""), Ok(Options::default()));
+        assert_eq!(
+            args("-d 3 --part 2 -n find_outlier2 -i - --all"),
+            Ok(Options {
+                day: Some(3),
+                part: Some(2),
+                name: Some(String::from("find_outlier2")),
+                input: Some(String::from("-")),
+                all: true,
+                ..Options::default()
+            })
+        );
+        assert!(args("-d").is_err());
+        assert!(args("-d six").is_err());
+        assert!(args("-p 3").is_err());
+        assert!(args("--verbose").is_err());
+        assert!(args("-i day1.txt").is_err());
+        assert_eq!(
+            args("--verify -d 1"),
+            Ok(Options {
+                day: Some(1),
+                verify: true,
+                ..Options::default()
+            })
+        );
+        assert!(args("-v -n immutable").is_err());
+    }
+
+    #[test]
+    fn test_select() {
+        assert_eq!(labels(""), vec!["Day 6 - Part 1", "Day 6 - Part 2"]);
+        assert_eq!(labels("-d 1 -p 2"), vec!["Day 1 - Part 2"]);
+        assert_eq!(
+            labels("-d 1 -p 2 --all"),
+            vec![
+                "Day 1 - Part 2",
+                "Day 1 - Part 2 - inline",
+                "Day 1 - Part 2 - immutable"
+            ]
+        );
+        assert_eq!(
+            labels("-d 3"),
+            vec!["Day 3 - Part 1", "Day 3 - Part 2 - find_outlier"]
+        );
+        assert_eq!(
+            labels("-d 3 -n find_outlier2"),
+            vec!["Day 3 - Part 2 - find_outlier2"]
+        );
+        assert_eq!(labels("--all").len(), registry::solutions().len());
+        assert!(labels("-d 1 -n missing").is_empty());
+    }
+}
@@ -0,0 +1,594 @@
+use std::any::Any;
+use std::convert::TryFrom;
+use std::fmt;
+use std::time::Duration;
+use std::time::Instant;
+
+use day1;
+use day2;
+use day3;
+use day4;
+use day5;
+use day6;
+
+// Answer is what a solution gives for a puzzle input, either a number or some
+// text, e.g. a box ID
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum Answer {
+    Int(i64),
+    // A number above i64::MAX. Smaller ones are always Int, so equal answers
+    // compare equal.
+    UInt(u64),
+    Str(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Int(n) => write!(f, "{}", n),
+            Answer::UInt(n) => write!(f, "{}", n),
+            Answer::Str(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+impl From<i32> for Answer {
+    fn from(n: i32) -> Self {
+        Answer::Int(i64::from(n))
+    }
+}
+
+impl From<u32> for Answer {
+    fn from(n: u32) -> Self {
+        Answer::Int(i64::from(n))
+    }
+}
+
+impl From<u64> for Answer {
+    fn from(n: u64) -> Self {
+        i64::try_from(n).map_or(Answer::UInt(n), Answer::Int)
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(n: usize) -> Self {
+        Answer::from(n as u64)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Str(s)
+    }
+}
+
+// Gets the answer of a solution that can fail, or the error as text
+fn solved<T: Into<Answer>>(solved: day6::Solved<T>) -> Result<Answer, String> {
+    solved.0.map(Into::into).map_err(|e| e.to_string())
+}
+
+// Input is a puzzle input once parsed by a day's generator, or the raw text
+// for solutions that parse it themselves
+pub type Input = Box<dyn Any>;
+
+// Gets the parsed input a solution expects, panicking if it came from another
+// day's generator
+fn parsed<T: Any>(input: &Input) -> &T {
+    input
+        .downcast_ref()
+        .expect("input parsed by another day's generator")
+}
+
+// Solution is one implementation of a day's part. Running it parses the raw
+// input with the day's generator, if it has one, then solves the puzzle, which
+// fails if the input is beyond what the solution can handle.
+pub struct Solution {
+    pub day: u32,
+    pub part: u32,
+    // The alternate's name, as given to #[aoc], or None for the default
+    pub name: Option<&'static str>,
+    pub description: &'static str,
+    // Whether this answers a variation on the puzzle's question, e.g. a
+    // length instead of the polymer itself, so can't be cross-checked against
+    // the other implementations
+    pub variant: bool,
+    // The day's #[aoc_generator], or None if the solution parses the raw
+    // input itself
+    pub generator: Option<&'static str>,
+    generate: fn(&str) -> Input,
+    solve: fn(&Input) -> Result<Answer, String>,
+}
+
+impl Solution {
+    // Parses the input, which should have no trailing newline, with the
+    // day's generator, or keeps it as it is if there's none
+    pub fn generate(&self, input: &str) -> Input {
+        (self.generate)(input)
+    }
+
+    // Solves the puzzle for an input from generate. Panics if the input came
+    // from a solution with a different generator.
+    pub fn solve(&self, input: &Input) -> Result<Answer, String> {
+        (self.solve)(input)
+    }
+
+    // Solves the puzzle for the input, which should have no trailing newline
+    pub fn run(&self, input: &str) -> Result<Answer, String> {
+        self.solve(&self.generate(input))
+    }
+
+    // Whether this is the solution run when no name is given: the unnamed
+    // one, or else the first listed for its day and part
+    pub fn is_default(&self) -> bool {
+        SOLUTIONS
+            .iter()
+            .filter(|s| s.day == self.day && s.part == self.part)
+            .min_by_key(|s| s.name.is_some())
+            .is_some_and(|s| s.name == self.name)
+    }
+
+    // Gets a heading like "Day 1 - Part 2 - immutable"
+    pub fn label(&self) -> String {
+        match self.name {
+            Some(name) => format!("Day {} - Part {} - {}", self.day, self.part, name),
+            None => format!("Day {} - Part {}", self.day, self.part),
+        }
+    }
+}
+
+impl fmt::Debug for Solution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Solution")
+            .field("day", &self.day)
+            .field("part", &self.part)
+            .field("name", &self.name)
+            .field("description", &self.description)
+            .field("variant", &self.variant)
+            .field("generator", &self.generator)
+            .finish()
+    }
+}
+
+// Every #[aoc] solution, in day and part order, listed by hand alongside the
+// attributes. test_examples runs each one.
+static SOLUTIONS: &[Solution] = &[
+    Solution {
+        day: 1,
+        part: 1,
+        name: None,
+        description: "Sums the frequency changes",
+        variant: false,
+        generator: Some("number_generator"),
+        generate: |input| Box::new(day1::number_generator(input)),
+        solve: |input| Ok(day1::part1_i32(parsed::<Vec<i32>>(input)).into()),
+    },
+    Solution {
+        day: 1,
+        part: 1,
+        name: Some("Chars"),
+        description: "Sums the frequency changes, parsing each line as it goes",
+        variant: false,
+        generator: None,
+        generate: |input| Box::new(String::from(input)),
+        solve: |input| Ok(day1::part1_chars(parsed::<String>(input)).into()),
+    },
+    Solution {
+        day: 1,
+        part: 2,
+        name: None,
+        description:
+            "Finds the first frequency reached twice, tracking seen frequencies in a HashSet",
+        variant: false,
+        generator: Some("number_generator"),
+        generate: |input| Box::new(day1::number_generator(input)),
+        solve: |input| Ok(day1::part2(parsed::<Vec<i32>>(input)).into()),
+    },
+    Solution {
+        day: 1,
+        part: 2,
+        name: Some("inline"),
+        description: "Finds the first frequency reached twice, in a single loop",
+        variant: false,
+        generator: None,
+        generate: |input| Box::new(String::from(input)),
+        solve: |input| Ok(day1::part2_chars(parsed::<String>(input)).into()),
+    },
+    Solution {
+        day: 1,
+        part: 2,
+        name: Some("immutable"),
+        description: "Finds the first frequency reached twice, with persistent state",
+        variant: false,
+        generator: Some("number_generator"),
+        generate: |input| Box::new(day1::number_generator(input)),
+        solve: |input| Ok(day1::part2_immutable(parsed::<Vec<i32>>(input)).into()),
+    },
+    Solution {
+        day: 2,
+        part: 1,
+        name: None,
+        description: "Multiplies the IDs with a letter twice by those with a letter three times",
+        variant: false,
+        generator: Some("code_generator"),
+        generate: |input| Box::new(day2::code_generator(input)),
+        solve: |input| Ok(day2::part1_checksum(parsed::<Vec<String>>(input)).into()),
+    },
+    Solution {
+        day: 2,
+        part: 2,
+        name: None,
+        description: "Finds the letters shared by the two IDs differing by one character",
+        variant: false,
+        generator: Some("code_generator"),
+        generate: |input| Box::new(day2::code_generator(input)),
+        solve: |input| {
+            Ok(day2::part2_char_difference_closest_strings(parsed::<Vec<String>>(input)).into())
+        },
+    },
+    Solution {
+        day: 3,
+        part: 1,
+        name: None,
+        description: "Counts the square inches of fabric within two or more claims",
+        variant: false,
+        generator: Some("fabric_square_generator"),
+        generate: |input| Box::new(day3::fabric_square_generator(input)),
+        solve: |input| {
+            Ok(day3::day3_part1_find_overlapped_area(parsed::<Vec<day3::Rectangle>>(input)).into())
+        },
+    },
+    Solution {
+        day: 3,
+        part: 2,
+        name: Some("find_outlier"),
+        description: "Finds the claim overlapping no other, by comparing claims pairwise",
+        variant: false,
+        generator: Some("fabric_square_generator"),
+        generate: |input| Box::new(day3::fabric_square_generator(input)),
+        solve: |input| {
+            Ok(day3::day3_part2_find_outlier(parsed::<Vec<day3::Rectangle>>(input)).into())
+        },
+    },
+    Solution {
+        day: 3,
+        part: 2,
+        name: Some("find_outlier2"),
+        description: "Finds the claim overlapping no other, by counting claims per square",
+        variant: false,
+        generator: Some("fabric_square_generator"),
+        generate: |input| Box::new(day3::fabric_square_generator(input)),
+        solve: |input| {
+            Ok(day3::day3_part2_find_outlier2(parsed::<Vec<day3::Rectangle>>(input)).into())
+        },
+    },
+    Solution {
+        day: 4,
+        part: 1,
+        name: None,
+        description: "Multiplies the guard most asleep by the minute they are most often asleep",
+        variant: false,
+        generator: Some("event_generator"),
+        generate: |input| Box::new(day4::event_generator(input)),
+        solve: |input| Ok(day4::day4_part1(parsed::<Vec<day4::Event>>(input)).into()),
+    },
+    Solution {
+        day: 4,
+        part: 2,
+        name: None,
+        description:
+            "Multiplies the guard by the minute they are most often asleep on, of any guard",
+        variant: false,
+        generator: Some("event_generator"),
+        generate: |input| Box::new(day4::event_generator(input)),
+        solve: |input| Ok(day4::day4_part2(parsed::<Vec<day4::Event>>(input)).into()),
+    },
+    Solution {
+        day: 5,
+        part: 1,
+        name: None,
+        description: "Counts the units left once the polymer fully reacts",
+        variant: false,
+        generator: None,
+        generate: |input| Box::new(String::from(input)),
+        solve: |input| Ok(day5::day5_part1(parsed::<String>(input)).into()),
+    },
+    Solution {
+        day: 5,
+        part: 2,
+        name: None,
+        description:
+            "Finds the unit type whose removal leaves the shortest polymer, and that polymer",
+        variant: false,
+        generator: None,
+        generate: |input| Box::new(String::from(input)),
+        solve: |input| Ok(day5::day5_part2(parsed::<String>(input)).into()),
+    },
+    Solution {
+        day: 5,
+        part: 2,
+        name: Some("length"),
+        description: "Counts the units in the shortest polymer left by removing one unit type",
+        variant: true,
+        generator: None,
+        generate: |input| Box::new(String::from(input)),
+        solve: |input| Ok(day5::day5_part2_length(parsed::<String>(input)).into()),
+    },
+    Solution {
+        day: 6,
+        part: 1,
+        name: None,
+        description: "Finds the largest finite area closest to one coordinate",
+        variant: false,
+        generator: Some("pair_generator"),
+        generate: |input| Box::new(day6::pair_generator(input)),
+        solve: |input| solved(day6::day6_part1(parsed::<Vec<day6::Pair>>(input))),
+    },
+    Solution {
+        day: 6,
+        part: 2,
+        name: None,
+        description: "Counts the locations within a total distance of 10000 of every coordinate",
+        variant: false,
+        generator: Some("pair_generator"),
+        generate: |input| Box::new(day6::pair_generator(input)),
+        solve: |input| solved(day6::day6_part2(parsed::<Vec<day6::Pair>>(input))),
+    },
+];
+
+// Gets every solution, in day, part and then default-first order
+pub fn solutions() -> &'static [Solution] {
+    SOLUTIONS
+}
+
+// Finds a solution by day, part and alternate name, or the default one if no
+// name is given
+pub fn find(day: u32, part: u32, name: Option<&str>) -> Option<&'static Solution> {
+    SOLUTIONS.iter().find(|s| {
+        s.day == day
+            && s.part == part
+            && match name {
+                Some(name) => s.name == Some(name),
+                None => s.is_default(),
+            }
+    })
+}
+
+// Gets every implementation of a day's part
+pub fn alternates(day: u32, part: u32) -> Vec<&'static Solution> {
+    SOLUTIONS
+        .iter()
+        .filter(|s| s.day == day && s.part == part)
+        .collect()
+}
+
+// Run is one solution's answer for an input, or why it failed, and how long it
+// took to parse the input and solve the puzzle
+#[derive(Debug, Clone)]
+pub struct Run {
+    pub solution: &'static Solution,
+    pub answer: Result<Answer, String>,
+    pub elapsed: Duration,
+}
+
+// CrossCheck holds the answers of every implementation of a day's part for
+// the same input
+#[derive(Debug, Clone)]
+pub struct CrossCheck {
+    pub day: u32,
+    pub part: u32,
+    // In registry order, so the default implementation comes first
+    pub runs: Vec<Run>,
+}
+
+impl CrossCheck {
+    // Whether every implementation gave the same answer, failing none
+    pub fn agrees(&self) -> bool {
+        self.runs.iter().all(|r| r.answer.is_ok())
+            && self.runs.windows(2).all(|w| w[0].answer == w[1].answer)
+    }
+}
+
+// Runs every implementation of a day's part on the input, other than
+// variants, so their answers can be compared
+pub fn cross_check(day: u32, part: u32, input: &str) -> CrossCheck {
+    let runs = alternates(day, part)
+        .into_iter()
+        .filter(|s| !s.variant)
+        .map(|solution| {
+            let start = Instant::now();
+            let answer = solution.run(input);
+            Run {
+                solution,
+                answer,
+                elapsed: start.elapsed(),
+            }
+        })
+        .collect();
+    CrossCheck { day, part, runs }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_answer() {
+        assert_eq!(Answer::from(-3i32), Answer::Int(-3));
+        assert_eq!(Answer::from(7usize), Answer::Int(7));
+        assert_eq!(Answer::from(1u64 << 63), Answer::UInt(1 << 63));
+        assert_eq!(Answer::from(u64::MAX).to_string(), "18446744073709551615");
+        assert_eq!(Answer::from(u64::MAX as usize), Answer::UInt(u64::MAX));
+        assert_eq!(Answer::from(String::from("fgij")).to_string(), "fgij");
+        assert_eq!(Answer::Int(-3).to_string(), "-3");
+    }
+
+    #[test]
+    fn test_find() {
+        assert_eq!(find(1, 2, None).unwrap().name, None);
+        assert_eq!(
+            find(1, 2, Some("immutable")).unwrap().label(),
+            "Day 1 - Part 2 - immutable"
+        );
+        assert_eq!(find(3, 2, None).unwrap().name, Some("find_outlier"));
+        assert!(find(1, 2, Some("missing")).is_none());
+        assert!(find(26, 1, None).is_none());
+        assert_eq!(alternates(1, 2).len(), 3);
+    }
+
+    #[test]
+    fn test_solutions_are_unique() {
+        for (i, a) in solutions().iter().enumerate() {
+            assert!(a.part == 1 || a.part == 2);
+            assert!(!a.description.is_empty());
+            for b in &solutions()[i + 1..] {
+                assert!((a.day, a.part, a.name) != (b.day, b.part, b.name));
+            }
+        }
+    }
+
+    const DAY4: &str = "[1518-11-01 00:00] Guard #10 begins shift
+[1518-11-01 00:05] falls asleep
+[1518-11-01 00:25] wakes up
+[1518-11-01 00:30] falls asleep
+[1518-11-01 00:55] wakes up
+[1518-11-01 23:58] Guard #99 begins shift
+[1518-11-02 00:40] falls asleep
+[1518-11-02 00:50] wakes up
+[1518-11-03 00:05] Guard #10 begins shift
+[1518-11-03 00:24] falls asleep
+[1518-11-03 00:29] wakes up
+[1518-11-04 00:02] Guard #99 begins shift
+[1518-11-04 00:36] falls asleep
+[1518-11-04 00:46] wakes up
+[1518-11-05 00:03] Guard #99 begins shift
+[1518-11-05 00:45] falls asleep
+[1518-11-05 00:55] wakes up";
+
+    const DAY6: &str = "1, 1\n1, 6\n8, 3\n3, 4\n5, 5\n8, 9";
+
+    #[test]
+    fn test_examples() {
+        // The example from each puzzle, for each part and alternate
+        let examples: &[(u32, u32, Option<&str>, &str, Answer)] = &[
+            (1, 1, None, "+1\n-2\n+3\n+1", Answer::Int(3)),
+            (1, 1, Some("Chars"), "+1\n-2\n+3\n+1", Answer::Int(3)),
+            (1, 2, None, "+3\n+3\n+4\n-2\n-4", Answer::Int(10)),
+            (1, 2, Some("inline"), "+3\n+3\n+4\n-2\n-4", Answer::Int(10)),
+            (
+                1,
+                2,
+                Some("immutable"),
+                "+3\n+3\n+4\n-2\n-4",
+                Answer::Int(10),
+            ),
+            (
+                2,
+                1,
+                None,
+                "abcdef\nbababc\nabbcde\nabcccd\naabcdd\nabcdee\nababab",
+                Answer::Int(12),
+            ),
+            (
+                2,
+                2,
+                None,
+                "abcde\nfghij\nklmno\npqrst\nfguij\naxcye\nwvxyz",
+                Answer::Str(String::from("fgij")),
+            ),
+            (
+                3,
+                1,
+                None,
+                "#1 @ 1,3: 4x4\n#2 @ 3,1: 4x4\n#3 @ 5,5: 2x2",
+                Answer::Int(4),
+            ),
+            (
+                3,
+                2,
+                Some("find_outlier"),
+                "#1 @ 1,3: 4x4\n#2 @ 3,1: 4x4\n#3 @ 5,5: 2x2",
+                Answer::Int(3),
+            ),
+            (
+                3,
+                2,
+                Some("find_outlier2"),
+                "#1 @ 1,3: 4x4\n#2 @ 3,1: 4x4\n#3 @ 5,5: 2x2",
+                Answer::Int(3),
+            ),
+            (4, 1, None, DAY4, Answer::Int(240)),
+            (4, 2, None, DAY4, Answer::Int(4455)),
+            (5, 1, None, "dabAcCaCBAcCcaDA", Answer::Int(10)),
+            (
+                5,
+                2,
+                None,
+                "dabAcCaCBAcCcaDA",
+                Answer::Str(String::from("c-daDA")),
+            ),
+            (5, 2, Some("length"), "dabAcCaCBAcCcaDA", Answer::Int(4)),
+            (6, 1, None, DAY6, Answer::Int(17)),
+            // Every location of the example is well within 10000
+            (6, 2, None, DAY6, Answer::Int(5_554_416)),
+        ];
+        assert_eq!(examples.len(), solutions().len());
+        for solution in solutions() {
+            let (.., input, answer) = examples
+                .iter()
+                .find(|e| (e.0, e.1, e.2) == (solution.day, solution.part, solution.name))
+                .unwrap_or_else(|| panic!("no example for {}", solution.label()));
+            assert_eq!(
+                solution.run(input).as_ref(),
+                Ok(answer),
+                "{}",
+                solution.label()
+            );
+
+            // Solutions with the same generator can share its output
+            for other in alternates(solution.day, solution.part) {
+                if other.generator.is_some() && other.generator == solution.generator {
+                    assert_eq!(
+                        other.solve(&solution.generate(input)).as_ref(),
+                        Ok(answer),
+                        "{}",
+                        other.label()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_solve_other_input() {
+        let input = find(1, 1, None).unwrap().generate("+1");
+        let _ = find(3, 1, None).unwrap().solve(&input);
+    }
+
+    #[test]
+    fn test_cross_check() {
+        let check = cross_check(1, 2, "+3\n+3\n+4\n-2\n-4");
+        assert_eq!(check.runs.len(), 3);
+        assert!(check.agrees());
+        assert!(check.runs.iter().all(|r| r.answer == Ok(Answer::Int(10))));
+
+        // Once the first claim and the one it overlaps are dropped, the
+        // pairwise search no longer sees that the third claim overlaps too
+        let check = cross_check(
+            3,
+            2,
+            "#1 @ 0,0: 2x2\n#2 @ 1,1: 2x2\n#3 @ 2,2: 2x2\n#4 @ 10,10: 1x1",
+        );
+        assert!(!check.agrees());
+        let answers: Vec<Result<Answer, String>> =
+            check.runs.iter().map(|r| r.answer.clone()).collect();
+        assert_eq!(answers, vec![Ok(Answer::Int(3)), Ok(Answer::Int(4))]);
+
+        let check = cross_check(5, 2, "dabAcCaCBAcCcaDA");
+        assert_eq!(check.runs.len(), 1);
+        assert!(check.agrees());
+
+        assert!(cross_check(26, 1, "").runs.is_empty());
+        assert!(cross_check(26, 1, "").agrees());
+    }
+}